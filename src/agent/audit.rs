@@ -0,0 +1,174 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rig::agent::{CancelSignal, PromptHook};
+use rig::completion::CompletionModel;
+use rig::wasm_compat::WasmCompatSend;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::config::de::deserialize_option_usize;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AuditConfig {
+    #[serde(rename = "audit_dir")]
+    pub dir: Option<String>,
+    #[serde(
+        rename = "audit_max_chars",
+        default,
+        deserialize_with = "deserialize_option_usize"
+    )]
+    pub max_chars: Option<usize>,
+}
+
+/// One line of the audit transcript: a single tool invocation and what came back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub timestamp: String,
+    pub tool: String,
+    pub call_id: Option<String>,
+    pub args: serde_json::Value,
+    pub output: Option<String>,
+    pub truncated: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Prefixes rig gives tool failures when it turns them into the text fed back to the model.
+const ERROR_PREFIXES: [&str; 4] = [
+    "Toolset error: ",
+    "ToolCallError: ",
+    "ToolNotFoundError: ",
+    "JsonError: ",
+];
+
+/// Start times of in-flight calls, keyed by tool name and raw arguments.
+type PendingCalls = HashMap<(String, String), VecDeque<Instant>>;
+
+/// Records every tool call made during a single prompt run to a JSONL file.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_chars: usize,
+    file: Arc<Mutex<File>>,
+    started: Arc<Mutex<PendingCalls>>,
+}
+
+impl AuditLog {
+    /// Open a fresh transcript for a run starting at `now`, or `None` when auditing is off.
+    pub fn create(config: &AuditConfig, now: DateTime<Utc>) -> anyhow::Result<Option<Self>> {
+        let Some(dir) = config
+            .dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+        else {
+            return Ok(None);
+        };
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Creating audit directory {}", dir.display()))?;
+
+        let stamp = now.format("%Y%m%dT%H%M%S%.3fZ");
+        let path = dir.join(format!("run-{}.jsonl", stamp));
+        let file = File::create(&path)
+            .with_context(|| format!("Creating audit log {}", path.display()))?;
+
+        Ok(Some(Self {
+            path,
+            max_chars: config.max_chars.unwrap_or(2000),
+            file: Arc::new(Mutex::new(file)),
+            started: Arc::default(),
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn start(&self, tool: &str, args: &str) {
+        let mut started = self.started.lock().unwrap();
+        started
+            .entry((tool.to_string(), args.to_string()))
+            .or_default()
+            .push_back(Instant::now());
+    }
+
+    fn finish(&self, tool: &str, call_id: Option<String>, args: &str, result: &str) {
+        let duration_ms = {
+            let mut started = self.started.lock().unwrap();
+            started
+                .get_mut(&(tool.to_string(), args.to_string()))
+                .and_then(|queue| queue.pop_front())
+                .map(|at| at.elapsed().as_millis() as u64)
+                .unwrap_or_default()
+        };
+
+        let is_error = ERROR_PREFIXES.iter().any(|p| result.starts_with(p));
+        let (output, truncated) = if is_error {
+            (None, false)
+        } else if result.chars().count() > self.max_chars {
+            (Some(result.chars().take(self.max_chars).collect()), true)
+        } else {
+            (Some(result.to_string()), false)
+        };
+
+        let record = AuditRecord {
+            timestamp: Utc::now().to_rfc3339(),
+            tool: tool.to_string(),
+            call_id,
+            args: serde_json::from_str(args)
+                .unwrap_or_else(|_| serde_json::Value::String(args.to_string())),
+            output,
+            truncated,
+            duration_ms,
+            error: is_error.then(|| result.to_string()),
+        };
+
+        // Auditing must never abort the run, so write failures are only logged.
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(err) => {
+                log::warn!("failed to serialise audit record: {}", err);
+                return;
+            }
+        };
+        let mut file = self.file.lock().unwrap();
+        if let Err(err) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+            log::warn!("failed to write audit log {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+impl<M> PromptHook<M> for AuditLog
+where
+    M: CompletionModel,
+{
+    fn on_tool_call(
+        &self,
+        tool_name: &str,
+        _tool_call_id: Option<String>,
+        args: &str,
+        _cancel_sig: CancelSignal,
+    ) -> impl Future<Output = ()> + WasmCompatSend {
+        self.start(tool_name, args);
+        async {}
+    }
+
+    fn on_tool_result(
+        &self,
+        tool_name: &str,
+        tool_call_id: Option<String>,
+        args: &str,
+        result: &str,
+        _cancel_sig: CancelSignal,
+    ) -> impl Future<Output = ()> + WasmCompatSend {
+        self.finish(tool_name, tool_call_id, args, result);
+        async {}
+    }
+}
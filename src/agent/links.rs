@@ -0,0 +1,146 @@
+use serde::Deserialize;
+
+use super::validate::{is_emoji, links};
+use crate::config::de::deserialize_bool;
+use crate::tools::web::{WebReadabilityTool, WebReadabilityToolError};
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct LinkCheckConfig {
+    #[serde(rename = "check_links", default, deserialize_with = "deserialize_bool")]
+    pub enabled: bool,
+}
+
+/// A link in the draft that did not resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// The entry heading the link appears under, as plain text.
+    pub entry: String,
+    pub url: String,
+    pub reason: String,
+}
+
+/// Request every distinct link in the draft's entries (the Editor Review Notes are skipped) and
+/// return the ones that fail. Hosts outside the web allowlist are not checked.
+pub async fn check_links(web: &WebReadabilityTool, draft: &str) -> Vec<BrokenLink> {
+    let mut seen = Vec::new();
+    let mut broken = Vec::new();
+    let mut entry = String::new();
+
+    for line in draft.lines() {
+        let trimmed = line.trim();
+        if let Some(text) = trimmed.strip_prefix("## ") {
+            if text.contains("Editor Review Notes") {
+                break;
+            }
+        }
+        if let Some(text) = trimmed.strip_prefix("### ") {
+            entry = heading_text(text);
+        }
+
+        for (_, url) in links(trimmed) {
+            if !(url.starts_with("http://") || url.starts_with("https://")) || seen.contains(&url) {
+                continue;
+            }
+            seen.push(url.clone());
+            match web.check_link(&url).await {
+                Ok(()) => log::debug!("link ok: {}", url),
+                Err(WebReadabilityToolError::DisallowedHost(host)) => {
+                    log::debug!("not checking {} (host {} not allowed)", url, host)
+                }
+                Err(WebReadabilityToolError::BrokenLink { reason, .. }) => {
+                    log::warn!("broken link {}: {}", url, reason);
+                    broken.push(BrokenLink {
+                        entry: entry.clone(),
+                        url,
+                        reason,
+                    });
+                }
+                Err(err) => broken.push(BrokenLink {
+                    entry: entry.clone(),
+                    url,
+                    reason: err.to_string(),
+                }),
+            }
+        }
+    }
+
+    broken
+}
+
+/// Add `broken` to the "Links to verify" list in the Editor Review Notes, creating the section
+/// (and the notes themselves) when the draft lacks them.
+pub fn append_link_notes(draft: &str, broken: &[BrokenLink]) -> String {
+    if broken.is_empty() {
+        return draft.to_string();
+    }
+    let items = broken
+        .iter()
+        .map(|b| {
+            format!(
+                "- [ ] [{}] — {} could not be reached ({})",
+                b.entry, b.url, b.reason
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines: Vec<String> = draft.trim_end().lines().map(str::to_string).collect();
+    let is_heading = |line: &str, level: &str, text: &str| {
+        line.trim()
+            .strip_prefix(level)
+            .is_some_and(|rest| rest.contains(text))
+    };
+
+    let Some(notes) = lines
+        .iter()
+        .position(|l| is_heading(l, "## ", "Editor Review Notes"))
+    else {
+        lines.extend([
+            String::new(),
+            "---".to_string(),
+            String::new(),
+            "## ✏️ Editor Review Notes".to_string(),
+            String::new(),
+            "### 🔗 Links to verify".to_string(),
+        ]);
+        lines.extend(items);
+        return format!("{}\n", lines.join("\n"));
+    };
+
+    match lines
+        .iter()
+        .skip(notes + 1)
+        .position(|l| is_heading(l, "### ", "Links to verify"))
+        .map(|offset| notes + 1 + offset)
+    {
+        Some(section) => {
+            // Insert after the section's last non-blank line, before the next heading.
+            let end = lines
+                .iter()
+                .skip(section + 1)
+                .position(|l| l.trim_start().starts_with('#'))
+                .map(|offset| section + 1 + offset)
+                .unwrap_or(lines.len());
+            let mut insert_at = end;
+            while insert_at > section + 1 && lines[insert_at - 1].trim().is_empty() {
+                insert_at -= 1;
+            }
+            lines.splice(insert_at..insert_at, items);
+        }
+        None => {
+            let mut block = vec![String::new(), "### 🔗 Links to verify".to_string()];
+            block.extend(items);
+            lines.splice(notes + 1..notes + 1, block);
+        }
+    }
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Plain-text form of an entry heading: emoji dropped and links replaced by their text.
+fn heading_text(heading: &str) -> String {
+    let mut text = heading.trim().to_string();
+    for (display, url) in links(heading) {
+        text = text.replace(&format!("[{}]({})", display, url), &display);
+    }
+    text.trim_start_matches(|c: char| is_emoji(c) || c == '\u{FE0F}' || c.is_whitespace())
+        .to_string()
+}
@@ -1,80 +1,203 @@
+pub mod audit;
+pub mod links;
+pub mod prefetch;
 pub mod prompt;
+pub mod provider;
+pub mod validate;
 
 use anyhow::Error;
+use std::future::Future;
+use std::future::IntoFuture;
+use std::pin::Pin;
 
-use prompt::build_initial_prompt;
+use audit::AuditLog;
+use chrono::Utc;
+use links::{append_link_notes, check_links};
+use prefetch::{render_prefetched, Prefetcher};
+use prompt::{build_prompt, load_prompt, PromptVars};
+use provider::LlmProvider;
+use validate::{allowed_emoji, correction_prompt, validate_with_emoji};
 
+use crate::cache::HttpCache;
 use crate::config::AppConfig;
 use crate::tools::discourse::DiscourseTool;
+use crate::tools::discourse_recent::DiscourseRecentTool;
+use crate::tools::github::GithubReleaseTool;
 use crate::tools::glean::GleanTool;
 use crate::tools::mailing_list::MailingListTool;
-use crate::tools::todoist::TodoistTasksTool;
+use crate::tools::manifest::{LinkClassifier, ManifestLink};
+use crate::tools::todoist::{TodoistTasksTool, TodoistToolError};
 use crate::tools::web::WebReadabilityTool;
 
 use rig::agent::Agent as RigAgent;
-use rig::client::CompletionClient;
-use rig::completion::Prompt;
-use rig::providers::gemini;
-use rig::providers::gemini::completion::CompletionModel;
+use rig::agent::AgentBuilder;
+use rig::completion::{CompletionModel, Message, Prompt, PromptError};
+
+const MAX_TURNS: usize = 20;
+
+type PromptFuture<'a> = Pin<Box<dyn Future<Output = Result<String, PromptError>> + Send + 'a>>;
+
+/// Object-safe view of a rig agent so `Agent` can hold one for any completion model.
+trait PromptBackend: Send + Sync {
+    fn prompt<'a>(
+        &'a self,
+        prompt: String,
+        history: &'a mut Vec<Message>,
+        max_turns: usize,
+        audit: Option<AuditLog>,
+    ) -> PromptFuture<'a>;
+}
+
+impl<M> PromptBackend for RigAgent<M>
+where
+    M: CompletionModel + 'static,
+{
+    fn prompt<'a>(
+        &'a self,
+        prompt: String,
+        history: &'a mut Vec<Message>,
+        max_turns: usize,
+        audit: Option<AuditLog>,
+    ) -> PromptFuture<'a> {
+        let request = Prompt::prompt(self, prompt)
+            .with_history(history)
+            .multi_turn(max_turns);
+        match audit {
+            Some(audit) => request.with_hook(audit).into_future(),
+            None => request.into_future(),
+        }
+    }
+}
 
 struct BuildResult {
-    agent: RigAgent<CompletionModel>,
+    agent: Box<dyn PromptBackend>,
+    base_prompt: String,
     discourse_hosts: Vec<String>,
+    discourse_sources: Vec<String>,
     mailing_list_names: Vec<String>,
+    todoist: TodoistTasksTool,
+    prefetcher: Option<Prefetcher>,
 }
 
 pub struct Agent {
-    agent: RigAgent<CompletionModel>,
+    agent: Box<dyn PromptBackend>,
     config: AppConfig,
+    base_prompt: String,
     discourse_hosts: Vec<String>,
+    discourse_sources: Vec<String>,
     mailing_list_names: Vec<String>,
+    todoist: TodoistTasksTool,
+    prefetcher: Option<Prefetcher>,
 }
 
 impl Agent {
+    /// Build an agent backed by the provider selected in `config.llm`, using the Todoist project,
+    /// glean directory, sources and prompt template of the profile `config` was loaded for.
     pub fn new(config: AppConfig) -> Result<Self, Error> {
-        let result = Self::build(&config)?;
+        match config.llm.provider {
+            LlmProvider::Gemini => {
+                let model = provider::gemini_model(&config)?;
+                Self::with_model(config, model)
+            }
+            LlmProvider::OpenAi => {
+                let model = provider::openai_model(&config)?;
+                Self::with_model(config, model)
+            }
+            LlmProvider::Anthropic => {
+                let model = provider::anthropic_model(&config)?;
+                Self::with_model(config, model)
+            }
+            LlmProvider::Ollama => {
+                let model = provider::ollama_model(&config)?;
+                Self::with_model(config, model)
+            }
+        }
+    }
+
+    /// Build an agent around an arbitrary completion model, wiring up the same tools
+    /// and context regardless of provider.
+    pub fn with_model<M>(config: AppConfig, model: M) -> Result<Self, Error>
+    where
+        M: CompletionModel + 'static,
+    {
+        let result = Self::build(&config, model)?;
         Ok(Self {
             agent: result.agent,
             config,
+            base_prompt: result.base_prompt,
             discourse_hosts: result.discourse_hosts,
+            discourse_sources: result.discourse_sources,
             mailing_list_names: result.mailing_list_names,
+            todoist: result.todoist,
+            prefetcher: result.prefetcher,
         })
     }
 
-    fn build(config: &AppConfig) -> Result<BuildResult, Error> {
-        let todoist_tool = TodoistTasksTool::new(config.todoist.clone())?;
-        let web_tool = WebReadabilityTool::new(config.web.clone())?;
+    fn build<M>(config: &AppConfig, model: M) -> Result<BuildResult, Error>
+    where
+        M: CompletionModel + 'static,
+    {
+        let base_prompt = load_prompt(&config.prompt)?;
+        let cache = HttpCache::new(config.cache.clone())?;
+        let web_tool = WebReadabilityTool::new(config.web.clone())?.with_cache(cache.clone());
+        let github_tool =
+            GithubReleaseTool::new(config.github.clone(), config.web.max_chars.unwrap_or(8000))?
+                .with_cache(cache.clone());
         let glean_tool = GleanTool::new(config.glean.clone())?;
         let glean_context = glean_tool.gather_context()?;
 
         let discourse_tool = DiscourseTool::new(
             config.discourse.clone(),
             config.web.max_chars.unwrap_or(8000),
-        );
+        )
+        .map(|t| t.with_cache(cache.clone()));
         let discourse_hosts = discourse_tool
             .as_ref()
             .map(|t| t.base_urls())
             .unwrap_or_default();
+        let todoist_tool = TodoistTasksTool::new(config.todoist.clone())?.with_classifier(
+            LinkClassifier::new(discourse_tool.clone(), Some(web_tool.clone())),
+        );
+        let prefetcher = config.prefetch.enabled.then(|| {
+            Prefetcher::new(
+                &config.prefetch,
+                todoist_tool.clone(),
+                config.todoist.project_section.clone(),
+                web_tool.clone(),
+                github_tool.clone(),
+                discourse_tool.clone(),
+            )
+        });
 
-        let gemini_client = gemini::Client::new(&config.gemini_api_key)?;
-
-        let mut agent_builder = gemini_client
-            .agent(&config.gemini_model)
+        let mut agent_builder = AgentBuilder::new(model)
             .preamble(
                 "You are a concise assistant that helps summarize and organize tasks for newsagent.",
             )
-            .tool(todoist_tool)
+            .tool(todoist_tool.clone())
             .tool(web_tool)
+            .tool(github_tool)
             .tool(glean_tool);
 
         if let Some(tool) = discourse_tool {
             agent_builder = agent_builder.tool(tool);
         }
 
+        let discourse_recent_tool = DiscourseRecentTool::new(config.discourse.clone())?
+            .map(|t| t.with_cache(cache.clone()));
+        let discourse_sources = discourse_recent_tool
+            .as_ref()
+            .map(|t| t.source_labels())
+            .unwrap_or_default();
+
+        if let Some(tool) = discourse_recent_tool {
+            agent_builder = agent_builder.tool(tool);
+        }
+
         let mailing_list_tool = MailingListTool::new(
             config.mailing_list.clone(),
             config.web.max_chars.unwrap_or(8000),
-        );
+        )
+        .map(|t| t.with_cache(cache));
         let mailing_list_names = mailing_list_tool
             .as_ref()
             .map(|t| t.list_names().to_vec())
@@ -91,24 +214,112 @@ impl Agent {
             ));
         }
 
+        if let Some(max_tokens) = config.llm.max_tokens {
+            agent_builder = agent_builder.max_tokens(max_tokens);
+        }
+
         Ok(BuildResult {
-            agent: agent_builder.build(),
+            agent: Box::new(agent_builder.build()),
+            base_prompt,
             discourse_hosts,
+            discourse_sources,
             mailing_list_names,
+            todoist: todoist_tool,
+            prefetcher,
         })
     }
 
+    /// The link manifest for the configured Todoist section, classified for this agent's tools.
+    pub async fn manifest(&self) -> Result<Vec<ManifestLink>, TodoistToolError> {
+        self.todoist
+            .manifest(self.config.todoist.project_section.as_deref())
+            .await
+    }
+
     pub async fn prompt(&self) -> Result<String, Error> {
-        let prompt = build_initial_prompt(
-            self.config.todoist.project_section.as_deref(),
-            &self.discourse_hosts,
-            &self.mailing_list_names,
+        self.prompt_with_manifest(None).await
+    }
+
+    /// Like [`Agent::prompt`], but prefetch from `links` (see [`Agent::manifest`]) instead of
+    /// asking Todoist for the tasks again.
+    pub async fn prompt_with_manifest(
+        &self,
+        links: Option<Vec<ManifestLink>>,
+    ) -> Result<String, Error> {
+        let prompt = build_prompt(
+            &self.base_prompt,
+            &PromptVars {
+                section: self.config.todoist.project_section.as_deref(),
+                discourse_hosts: &self.discourse_hosts,
+                discourse_sources: &self.discourse_sources,
+                mailing_list_names: &self.mailing_list_names,
+                month: Utc::now().format("%B %Y").to_string(),
+                audience: self.config.prompt.effective_audience(),
+            },
         );
+        let prompt = match &self.prefetcher {
+            Some(prefetcher) => {
+                let sources = match links {
+                    Some(links) => Ok(prefetcher.fetch_all(&links).await),
+                    None => prefetcher.run().await,
+                };
+                match sources {
+                    Ok(sources) => format!(
+                        "{}\n\n{}",
+                        prompt,
+                        render_prefetched(&sources, prefetcher.max_chars())
+                    )
+                    .trim_end()
+                    .to_string(),
+                    Err(err) => {
+                        log::warn!("skipping prefetch: {}", err);
+                        prompt
+                    }
+                }
+            }
+            None => prompt,
+        };
+        let audit = AuditLog::create(&self.config.audit, Utc::now())?;
+        if let Some(audit) = audit.as_ref() {
+            log::info!("recording tool calls to {}", audit.path().display());
+        }
         log::info!("sending prompt to model");
-        self.agent
-            .prompt(prompt)
-            .multi_turn(20)
-            .await
-            .map_err(Error::from)
+        let mut history = Vec::new();
+        let mut response = self
+            .agent
+            .prompt(prompt, &mut history, MAX_TURNS, audit.clone())
+            .await?;
+
+        let emoji = allowed_emoji(&self.base_prompt);
+        let violations = validate_with_emoji(&response, &emoji);
+        if !violations.is_empty() && self.config.validate.self_correct {
+            log::info!(
+                "asking model to fix {} format violation(s)",
+                violations.len()
+            );
+            response = self
+                .agent
+                .prompt(
+                    correction_prompt(&violations),
+                    &mut history,
+                    MAX_TURNS,
+                    audit,
+                )
+                .await?;
+        }
+        for violation in validate_with_emoji(&response, &emoji) {
+            log::warn!("format violation: {}", violation);
+        }
+
+        if self.config.links.enabled {
+            if self.config.cache.offline {
+                log::info!("skipping link check in offline mode");
+            } else {
+                let web = WebReadabilityTool::new(self.config.web.clone())?;
+                let broken = check_links(&web, &response).await;
+                response = append_link_notes(&response, &broken);
+            }
+        }
+        Ok(response)
     }
 }
@@ -0,0 +1,217 @@
+use futures::stream::{self, StreamExt};
+use rig::tool::Tool;
+use serde::Deserialize;
+use url::Url;
+
+use crate::config::de::{deserialize_bool, deserialize_option_usize};
+use crate::tools::discourse::{DiscourseArgs, DiscourseTool};
+use crate::tools::github::{GithubReleaseArgs, GithubReleaseTool};
+use crate::tools::manifest::ManifestLink;
+use crate::tools::todoist::{TodoistTasksTool, TodoistToolError};
+use crate::tools::web::{HostLimiter, WebReadabilityArgs, WebReadabilityTool};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_MAX_CHARS: usize = 40_000;
+/// Sources that would get fewer characters than this are listed without content instead.
+const MIN_SOURCE_CHARS: usize = 500;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PrefetchConfig {
+    #[serde(rename = "prefetch", default, deserialize_with = "deserialize_bool")]
+    pub enabled: bool,
+    #[serde(
+        rename = "prefetch_concurrency",
+        default,
+        deserialize_with = "deserialize_option_usize"
+    )]
+    pub concurrency: Option<usize>,
+    /// Total characters of fetched content to put in the prompt.
+    #[serde(
+        rename = "prefetch_max_chars",
+        default,
+        deserialize_with = "deserialize_option_usize"
+    )]
+    pub max_chars: Option<usize>,
+}
+
+impl PrefetchConfig {
+    pub fn effective_concurrency(&self) -> usize {
+        self.concurrency
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_CONCURRENCY)
+    }
+
+    pub fn effective_max_chars(&self) -> usize {
+        self.max_chars
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_MAX_CHARS)
+    }
+}
+
+/// A task link fetched ahead of the model run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefetchedSource {
+    pub url: String,
+    pub tool: &'static str,
+    /// The tool output as JSON, or the error the tool returned.
+    pub content: Result<String, String>,
+}
+
+/// Fetches every task link through the tool the manifest names, so the model starts with the
+/// sources in its prompt instead of spending a turn on each one.
+#[derive(Clone)]
+pub struct Prefetcher {
+    todoist: TodoistTasksTool,
+    section: Option<String>,
+    web: WebReadabilityTool,
+    github: GithubReleaseTool,
+    discourse: Option<DiscourseTool>,
+    limiter: HostLimiter,
+    concurrency: usize,
+    max_chars: usize,
+}
+
+impl Prefetcher {
+    /// `todoist` should carry the classifier for the same Discourse and web tools.
+    pub fn new(
+        config: &PrefetchConfig,
+        todoist: TodoistTasksTool,
+        section: Option<String>,
+        web: WebReadabilityTool,
+        github: GithubReleaseTool,
+        discourse: Option<DiscourseTool>,
+    ) -> Self {
+        let limiter = web.limiter().clone();
+        Self {
+            todoist,
+            section,
+            web,
+            github,
+            discourse,
+            limiter,
+            concurrency: config.effective_concurrency(),
+            max_chars: config.effective_max_chars(),
+        }
+    }
+
+    /// The prompt budget for fetched content; see [`render_prefetched`].
+    pub fn max_chars(&self) -> usize {
+        self.max_chars
+    }
+
+    /// Build the link manifest from Todoist and fetch every supported link.
+    pub async fn run(&self) -> Result<Vec<PrefetchedSource>, TodoistToolError> {
+        let links = self.todoist.manifest(self.section.as_deref()).await?;
+        Ok(self.fetch_all(&links).await)
+    }
+
+    /// Fetch `links` with at most `concurrency` requests in flight, in manifest order.
+    /// Unsupported links are skipped.
+    pub async fn fetch_all(&self, links: &[ManifestLink]) -> Vec<PrefetchedSource> {
+        let supported: Vec<(&str, &'static str)> = links
+            .iter()
+            .filter_map(|link| link.tool.map(|tool| (link.url.as_str(), tool)))
+            .collect();
+        log::info!(
+            "prefetching {} link(s), {} at a time",
+            supported.len(),
+            self.concurrency
+        );
+        stream::iter(supported)
+            .map(|(url, tool)| self.fetch(url, tool))
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    async fn fetch(&self, url: &str, tool: &'static str) -> PrefetchedSource {
+        // The web tool waits on the same limiter itself.
+        if tool != WebReadabilityTool::NAME {
+            if let Some(host) = Url::parse(url)
+                .ok()
+                .and_then(|u| u.host_str().map(String::from))
+            {
+                self.limiter.wait(&host).await;
+            }
+        }
+        let url_string = url.to_string();
+        let content = match tool {
+            GithubReleaseTool::NAME => to_json(
+                self.github
+                    .call(GithubReleaseArgs { url: url_string })
+                    .await,
+            ),
+            DiscourseTool::NAME => match &self.discourse {
+                Some(discourse) => to_json(
+                    discourse
+                        .call(DiscourseArgs {
+                            url: url_string,
+                            full_thread: false,
+                        })
+                        .await,
+                ),
+                None => Err("no Discourse instances are configured".to_string()),
+            },
+            _ => to_json(self.web.call(WebReadabilityArgs { url: url_string }).await),
+        };
+        if let Err(err) = &content {
+            log::warn!("prefetch of {} failed: {}", url, err);
+        }
+        PrefetchedSource {
+            url: url.to_string(),
+            tool,
+            content,
+        }
+    }
+}
+
+/// Serialize a tool result for the prompt, without the page `links` list, which can outgrow
+/// the content on link-heavy pages; the model can fetch the page itself when it needs them.
+fn to_json<T: serde::Serialize, E: std::fmt::Display>(
+    result: Result<T, E>,
+) -> Result<String, String> {
+    let output = result.map_err(|err| err.to_string())?;
+    let mut value = serde_json::to_value(&output).map_err(|err| err.to_string())?;
+    if let Some(object) = value.as_object_mut() {
+        object.remove("links");
+    }
+    serde_json::to_string_pretty(&value).map_err(|err| err.to_string())
+}
+
+/// The prompt section listing the prefetched sources, or an empty string when there are none.
+/// At most `max_chars` characters of fetched content go in: the source that crosses the limit
+/// is cut short and the rest are listed by URL only, for the model to fetch itself.
+pub fn render_prefetched(sources: &[PrefetchedSource], max_chars: usize) -> String {
+    if sources.is_empty() {
+        return String::new();
+    }
+    let mut budget = max_chars;
+    let mut out = String::from(
+        "# Prefetched Sources\n\nThe links in the Todoist tasks have already been fetched with the tool named under each one. Use this content instead of calling that tool again for the same URL; only call tools for other URLs, for replies (`full_thread`), or where a fetch failed.\n",
+    );
+    for source in sources {
+        out.push_str(&format!("\n## {}\n\nTool: {}\n\n", source.url, source.tool));
+        match &source.content {
+            Ok(_) if budget < MIN_SOURCE_CHARS => out.push_str(
+                "Not included: the prefetch budget is used up. Call the tool for this URL.\n",
+            ),
+            Ok(json) if json.chars().count() > budget => {
+                let cut: String = json.chars().take(budget).collect();
+                budget = 0;
+                out.push_str(&format!(
+                    "```json\n{}\n```\n\nCut short to fit the prefetch budget. Call the tool for the full content.\n",
+                    cut
+                ));
+            }
+            Ok(json) => {
+                budget -= json.chars().count();
+                out.push_str(&format!("```json\n{}\n```\n", json));
+            }
+            Err(err) => out.push_str(&format!(
+                "Fetch failed: {}. Retry with the tool or flag the link in the Editor Review Notes.\n",
+                err
+            )),
+        }
+    }
+    out
+}
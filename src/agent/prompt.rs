@@ -1,9 +1,82 @@
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PromptConfig {
+    /// Markdown file to use in place of the built-in [`PROMPT`].
+    #[serde(rename = "prompt_template")]
+    pub template: Option<String>,
+    /// Value of `{{audience}}`; defaults to [`DEFAULT_AUDIENCE`].
+    #[serde(rename = "prompt_audience")]
+    pub audience: Option<String>,
+}
+
+impl PromptConfig {
+    pub fn effective_audience(&self) -> &str {
+        self.audience
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .unwrap_or(DEFAULT_AUDIENCE)
+    }
+}
+
+pub const DEFAULT_AUDIENCE: &str =
+    "Engineering Executives and their teams working on Ubuntu, Juju, and Charmed Operators";
+
+/// Variables a prompt template may reference as `{{name}}`.
+pub const TEMPLATE_VARIABLES: &[&str] = &[
+    "audience",
+    "discourse_hosts",
+    "mailing_lists",
+    "month",
+    "todoist_section",
+];
+
+/// Values substituted into a prompt template.
+#[derive(Debug, Clone, Default)]
+pub struct PromptVars<'a> {
+    pub section: Option<&'a str>,
+    pub discourse_hosts: &'a [String],
+    /// Categories and tags watched by `discourse_recent`.
+    pub discourse_sources: &'a [String],
+    pub mailing_list_names: &'a [String],
+    /// Month the newsletter covers, e.g. "October 2026".
+    pub month: String,
+    pub audience: &'a str,
+}
+
+impl PromptVars<'_> {
+    fn get(&self, name: &str) -> String {
+        let list = |values: &[String]| {
+            if values.is_empty() {
+                "(none)".to_string()
+            } else {
+                values.join(", ")
+            }
+        };
+        match name {
+            "audience" => self.audience.to_string(),
+            "discourse_hosts" => list(self.discourse_hosts),
+            "mailing_lists" => list(self.mailing_list_names),
+            "month" => self.month.clone(),
+            "todoist_section" => self
+                .section
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .unwrap_or("(all)")
+                .to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
 pub const PROMPT: &str = r#"
 # Role & Audience
 
 You are a senior technical writer drafting the **Tech Updates** section of an internal newsletter
-for an Engineering Executive at Canonical. The audience is Engineering Executives and their teams
-working on Ubuntu, Juju, and Charmed Operators.
+for an Engineering Executive at Canonical. The audience is {{audience}}.
 
 You will be given a set of raw URLs — GitHub releases, Discourse posts, blog entries — via a
 Todoist task list. Your job is to synthesise them into engaging, readable newsletter entries.
@@ -12,19 +85,40 @@ Todoist task list. Your job is to synthesise them into engaging, readable newsle
 
 You have the following tools. Use them — do not attempt to browse or verify anything manually.
 
-- **todoist_tasks** — fetch the list of tasks (URLs to cover). Call this first.
+- **todoist_tasks** — fetch the list of tasks (URLs to cover). Call this first. Each task may
+  carry **Labels**, a **Priority**, a **Due** date and editor **Comment**s; see "Editor Hints"
+  below. The `links` manifest lists every distinct URL in the tasks with its kind and the tool to
+  read it with; use that tool, and flag any link marked `unsupported` in the Editor Review Notes
+  instead of trying to fetch it.
 - **browse_web** — fetch and extract readable content from a URL. Use this to read release notes,
   blog posts, changelogs, and documentation pages. Call it on every URL you need to summarise.
+  It returns the page as markdown plus a `links` list of the links on the page with their anchor
+  text (capped; `omitted_links` counts the rest); take PR, issue and documentation URLs from there
+  rather than reconstructing them.
+- **github_release** — fetch a GitHub release through the REST API. **Always use this instead of
+  browse_web for `github.com/<owner>/<repo>/releases/tag/<tag>` URLs** — it returns the tag,
+  name, published date, author, the release notes as markdown and the linked PR numbers.
 - **local_markdown_context** — retrieve local markdown files for style reference.
 - **discourse_fetch** — fetch content from configured Discourse instances via the structured JSON
   API. **Always use this instead of browse_web for any Discourse URL** — it returns cleaner,
   more complete content and works without authentication for public posts. When an API key is
-  configured it can also access private/restricted content. See the dynamic hints below for
-  which hosts are configured.
+  configured it can also access private/restricted content. Pass `full_thread: true` on
+  announcement topics to also read the replies, where maintainers often post follow-up
+  corrections. See the dynamic hints below for which hosts are configured.
+- **discourse_recent** — list topics created recently in watched Discourse categories and tags.
+  Takes no arguments. **Always call this tool** when it is configured, so announcements nobody
+  added to Todoist are not missed.
 - **mailing_list_threads** — fetch recent discussion threads from configured Ubuntu mailing lists.
-  Returns deduplicated threads from the last 30 days. This tool takes no arguments. **Always call
-  this tool** when mailing lists are configured — do not wait for Todoist tasks to reference
-  mailing lists.
+  Returns deduplicated threads from the configured lookback window (30 days unless changed). Pass
+  `since` and `until` (`YYYY-MM-DD`) when the issue covers a different period, e.g. two months for
+  a bi-monthly issue or last month when regenerating a late draft. Threads come ranked by an
+  activity `score` and capped; `omitted` says how many more matched. Narrow busy lists with
+  `keywords`, `authors`, `lists`/`exclude_lists` and `min_messages` rather than raising `limit`.
+  Each thread has the opening message as `summary` and a date-ordered `replies` outline (author,
+  date, opening lines, quotes and signatures removed); use it to report where a discussion ended
+  up, not just how it started.
+  **Always call this tool** when mailing lists are configured — do not wait for Todoist tasks to
+  reference mailing lists.
 
 When a URL needs to be read, call the appropriate tool. If you cannot fetch a URL, note it in the
 Editor Review Notes (see below) and write what you can from the task title alone.
@@ -256,6 +350,18 @@ Order entries by product significance (most important first):
 
 Major milestones (GA releases, security fixes) may be promoted to the top regardless of product.
 
+## Editor Hints
+
+The editors annotate Todoist tasks. Honour these hints over your own judgement:
+
+- Label `security` — promote the entry to the top of the section and mention the fix plainly.
+- Label `tier1`, `tier2`, `tier3` or `tier4` — use that tier for the entry.
+- Label `skip` — do not write an entry for the task and do not fetch its URLs.
+- Priority `p1` or `p2` — the editors consider the item important; place it early within its
+  product group and do not merge it away.
+- Comments — extra context from the editors (angles to cover, related links, caveats). Use them,
+  but never quote them verbatim.
+
 # Constraints
 
 Do NOT:
@@ -328,11 +434,109 @@ Get all the details in the [release notes](https://github.com/canonical/pebble/r
 ```
 "#;
 
+/// The prompt template for `config`: the contents of the template file when one is set,
+/// otherwise the built-in [`PROMPT`]. Fails if the template uses an unknown variable.
+pub fn load_prompt(config: &PromptConfig) -> anyhow::Result<String> {
+    match config
+        .template
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        Some(path) => {
+            let template = fs::read_to_string(path)
+                .with_context(|| format!("Reading prompt template {}", path))?;
+            check_template(&template)
+                .with_context(|| format!("Invalid prompt template {}", path))?;
+            Ok(template)
+        }
+        None => Ok(PROMPT.to_string()),
+    }
+}
+
+/// Ensure every `{{...}}` placeholder in `template` names one of [`TEMPLATE_VARIABLES`].
+pub fn check_template(template: &str) -> anyhow::Result<()> {
+    for (index, line) in template.lines().enumerate() {
+        for placeholder in placeholders(line) {
+            let name = placeholder?;
+            if !TEMPLATE_VARIABLES.contains(&name) {
+                return Err(anyhow!(
+                    "line {}: unknown variable `{{{{{}}}}}` (expected one of: {})",
+                    index + 1,
+                    name,
+                    TEMPLATE_VARIABLES.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Placeholder names in `line`, or an error for a `{{` without a closing `}}`.
+fn placeholders(line: &str) -> Vec<anyhow::Result<&str>> {
+    let mut found = Vec::new();
+    let mut rest = line;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        match after.find("}}") {
+            Some(close) => {
+                found.push(Ok(after[..close].trim()));
+                rest = &after[close + 2..];
+            }
+            None => {
+                found.push(Err(anyhow!("unclosed `{{{{` in `{}`", line.trim())));
+                break;
+            }
+        }
+    }
+    found
+}
+
+/// Substitute `vars` into a template that has passed [`check_template`].
+pub fn render_template(template: &str, vars: &PromptVars) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        out.push_str(&vars.get(after[..close].trim()));
+        rest = &after[close + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Render the built-in prompt for the current month and default audience, with run hints.
 pub fn build_initial_prompt(
     section: Option<&str>,
     discourse_hosts: &[String],
     mailing_list_names: &[String],
 ) -> String {
+    build_prompt(
+        PROMPT,
+        &PromptVars {
+            section,
+            discourse_hosts,
+            discourse_sources: &[],
+            mailing_list_names,
+            month: chrono::Utc::now().format("%B %Y").to_string(),
+            audience: DEFAULT_AUDIENCE,
+        },
+    )
+}
+
+/// Render `template` with `vars`, then append the run-specific hints for the Todoist section,
+/// Discourse hosts and mailing lists.
+pub fn build_prompt(template: &str, vars: &PromptVars) -> String {
+    let base = render_template(template, vars);
+    let section = vars.section;
+    let discourse_hosts = vars.discourse_hosts;
+    let discourse_sources = vars.discourse_sources;
+    let mailing_list_names = vars.mailing_list_names;
+
     let section_hint = section
         .filter(|s| !s.trim().is_empty())
         .map(|s| {
@@ -352,6 +556,15 @@ pub fn build_initial_prompt(
         )
     };
 
+    let discourse_recent_hint = if discourse_sources.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\n\nIMPORTANT: The discourse_recent tool watches these Discourse categories and tags: {}. You MUST call discourse_recent in addition to todoist_tasks — new topics there are not tracked in Todoist. Read promising topics with discourse_fetch and include notable announcements as newsletter entries; skip routine or off-topic posts.",
+            discourse_sources.join(", ")
+        )
+    };
+
     let mailing_list_hint = if mailing_list_names.is_empty() {
         String::new()
     } else {
@@ -362,7 +575,7 @@ pub fn build_initial_prompt(
     };
 
     format!(
-        "{}{}{}{}",
-        PROMPT, section_hint, discourse_hint, mailing_list_hint
+        "{}{}{}{}{}",
+        base, section_hint, discourse_hint, discourse_recent_hint, mailing_list_hint
     )
 }
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Context, Error};
+use rig::client::CompletionClient;
+use rig::client::Nothing;
+use rig::providers::{anthropic, gemini, ollama, openai};
+use serde::Deserialize;
+use std::fmt;
+
+use crate::config::de::deserialize_option_u64;
+use crate::config::AppConfig;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmProvider {
+    #[default]
+    Gemini,
+    /// Any endpoint speaking the OpenAI chat completions API (OpenAI, llama.cpp, vLLM...).
+    OpenAi,
+    Anthropic,
+    Ollama,
+}
+
+impl fmt::Display for LlmProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LlmProvider::Gemini => "gemini",
+            LlmProvider::OpenAi => "openai",
+            LlmProvider::Anthropic => "anthropic",
+            LlmProvider::Ollama => "ollama",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct LlmConfig {
+    #[serde(rename = "llm_provider", default)]
+    pub provider: LlmProvider,
+    #[serde(rename = "llm_api_key")]
+    pub api_key: Option<String>,
+    #[serde(rename = "llm_model")]
+    pub model: Option<String>,
+    #[serde(rename = "llm_base_url")]
+    pub base_url: Option<String>,
+    #[serde(
+        rename = "llm_max_tokens",
+        default,
+        deserialize_with = "deserialize_option_u64"
+    )]
+    pub max_tokens: Option<u64>,
+}
+
+impl LlmConfig {
+    fn api_key(&self) -> Option<&str> {
+        self.api_key.as_deref().filter(|v| !v.trim().is_empty())
+    }
+
+    fn base_url(&self) -> Option<&str> {
+        self.base_url
+            .as_deref()
+            .map(|v| v.trim().trim_end_matches('/'))
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// The model name to request from the configured provider.
+///
+/// `NEWSAGENT_LLM_MODEL` wins; the Gemini provider falls back to `NEWSAGENT_GEMINI_MODEL`.
+pub fn model_name(config: &AppConfig) -> Result<String, Error> {
+    if let Some(model) = config.llm.model.as_deref().filter(|v| !v.trim().is_empty()) {
+        return Ok(model.trim().to_string());
+    }
+    match config.llm.provider {
+        LlmProvider::Gemini => Ok(config.gemini_model.clone()),
+        provider => Err(anyhow!(
+            "NEWSAGENT_LLM_MODEL must be set for the {} provider",
+            provider
+        )),
+    }
+}
+
+pub(crate) fn gemini_model(
+    config: &AppConfig,
+) -> Result<gemini::completion::CompletionModel, Error> {
+    let api_key = config
+        .llm
+        .api_key()
+        .or(config.gemini_api_key.as_deref())
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| {
+            anyhow!("NEWSAGENT_GEMINI_API_KEY or NEWSAGENT_LLM_API_KEY must be set for the gemini provider")
+        })?;
+    let mut builder = gemini::Client::builder().api_key(api_key);
+    if let Some(base_url) = config.llm.base_url() {
+        builder = builder.base_url(base_url);
+    }
+    let client = builder.build().context("Failed to build Gemini client")?;
+    Ok(client.completion_model(model_name(config)?))
+}
+
+pub(crate) fn openai_model(config: &AppConfig) -> Result<openai::CompletionModel, Error> {
+    // Local OpenAI-compatible servers typically ignore the key, so only insist on
+    // one when talking to the real API.
+    let api_key = match (config.llm.api_key(), config.llm.base_url()) {
+        (Some(key), _) => key,
+        (None, Some(_)) => "",
+        (None, None) => {
+            return Err(anyhow!(
+                "NEWSAGENT_LLM_API_KEY must be set for the openai provider"
+            ))
+        }
+    };
+    let mut builder = openai::CompletionsClient::builder().api_key(api_key);
+    if let Some(base_url) = config.llm.base_url() {
+        builder = builder.base_url(base_url);
+    }
+    let client = builder.build().context("Failed to build OpenAI client")?;
+    Ok(client.completion_model(model_name(config)?))
+}
+
+pub(crate) fn anthropic_model(
+    config: &AppConfig,
+) -> Result<anthropic::completion::CompletionModel, Error> {
+    let api_key = config
+        .llm
+        .api_key()
+        .ok_or_else(|| anyhow!("NEWSAGENT_LLM_API_KEY must be set for the anthropic provider"))?;
+    let mut builder = anthropic::Client::builder().api_key(api_key);
+    if let Some(base_url) = config.llm.base_url() {
+        builder = builder.base_url(base_url);
+    }
+    let client = builder
+        .build()
+        .context("Failed to build Anthropic client")?;
+    Ok(client.completion_model(model_name(config)?))
+}
+
+pub(crate) fn ollama_model(config: &AppConfig) -> Result<ollama::CompletionModel, Error> {
+    let mut builder = ollama::Client::builder().api_key(Nothing);
+    if let Some(base_url) = config.llm.base_url() {
+        builder = builder.base_url(base_url);
+    }
+    let client = builder.build().context("Failed to build Ollama client")?;
+    Ok(client.completion_model(model_name(config)?))
+}
+
+/// Build the client for the configured provider and throw it away, so a missing API key
+/// or bad base URL fails the same way `Agent::new` would.
+pub(crate) fn check_model(config: &AppConfig) -> Result<(), Error> {
+    match config.llm.provider {
+        LlmProvider::Gemini => gemini_model(config).map(drop),
+        LlmProvider::OpenAi => openai_model(config).map(drop),
+        LlmProvider::Anthropic => anthropic_model(config).map(drop),
+        LlmProvider::Ollama => ollama_model(config).map(drop),
+    }
+}
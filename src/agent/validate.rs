@@ -0,0 +1,528 @@
+use serde::Deserialize;
+use std::fmt;
+
+use super::prompt::PROMPT;
+
+use crate::config::de::deserialize_bool;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ValidateConfig {
+    #[serde(
+        rename = "validate_self_correct",
+        default,
+        deserialize_with = "deserialize_bool"
+    )]
+    pub self_correct: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    TechUpdatesHeading,
+    BadHeading,
+    VersionPrefix,
+    AmericanSpelling,
+    UnknownEmoji,
+    EmojiInBody,
+    PlaceholderUrl,
+    MissingEditorNotes,
+}
+
+/// A single deviation from the output format described in the prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// 1-based line number, or 0 for problems with the document as a whole.
+    pub line: usize,
+    pub kind: ViolationKind,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// Heading emoji allowed by the emoji map in the built-in prompt.
+const EMOJI_MAP: &[&str] = &[
+    "🚀", "🪄", "📦", "🪨", "🚧", "🔍", "🔬", "💪", "🥳", "🍸", "⚒️", "🐍", "🐘", "🐬", "🛳️", "🚢",
+    "🔒", "🧪", "📚", "ℹ️", "📈", "🏢", "📰", "🤖", "🦝",
+];
+
+/// American spellings the prompt asks the model to avoid, with their British forms.
+const AMERICAN_SPELLINGS: &[(&str, &str)] = &[
+    ("analyze", "analyse"),
+    ("analyzed", "analysed"),
+    ("authorization", "authorisation"),
+    ("behavior", "behaviour"),
+    ("behaviors", "behaviours"),
+    ("canceled", "cancelled"),
+    ("canceling", "cancelling"),
+    ("center", "centre"),
+    ("centered", "centred"),
+    ("color", "colour"),
+    ("colors", "colours"),
+    ("customization", "customisation"),
+    ("customize", "customise"),
+    ("customized", "customised"),
+    ("favor", "favour"),
+    ("favorite", "favourite"),
+    ("gray", "grey"),
+    ("honor", "honour"),
+    ("labeled", "labelled"),
+    ("labeling", "labelling"),
+    ("maximize", "maximise"),
+    ("minimize", "minimise"),
+    ("modeling", "modelling"),
+    ("modernize", "modernise"),
+    ("optimization", "optimisation"),
+    ("optimize", "optimise"),
+    ("optimized", "optimised"),
+    ("organization", "organisation"),
+    ("organizations", "organisations"),
+    ("organize", "organise"),
+    ("organized", "organised"),
+    ("prioritize", "prioritise"),
+    ("prioritized", "prioritised"),
+    ("recognize", "recognise"),
+    ("recognized", "recognised"),
+    ("stabilization", "stabilisation"),
+    ("stabilize", "stabilise"),
+    ("standardize", "standardise"),
+    ("standardized", "standardised"),
+    ("summarize", "summarise"),
+    ("summarized", "summarised"),
+    ("utilize", "utilise"),
+    ("utilized", "utilised"),
+];
+
+/// Substrings that mark a link target as made up rather than found in the source material.
+const PLACEHOLDER_MARKERS: &[&str] = &["example.com", "example.org", "example.net", "placeholder"];
+
+/// Whole link targets that stand in for a URL the model did not find.
+const PLACEHOLDER_TARGETS: &[&str] = &["url", "link", "todo", "tbd"];
+
+/// Heading emoji `template` allows: the emoji map for the built-in prompt, otherwise every
+/// emoji the template mentions.
+pub fn allowed_emoji(template: &str) -> Vec<String> {
+    if template == PROMPT {
+        return EMOJI_MAP.iter().map(|e| e.to_string()).collect();
+    }
+    let mut found: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut joined = false;
+    let mut flush = |current: &mut String| {
+        if !current.is_empty() && !found.iter().any(|e| same_emoji(e, current)) {
+            found.push(current.clone());
+        }
+        current.clear();
+    };
+    for c in template.chars() {
+        if (c == '\u{FE0F}' || c == '\u{200D}') && !current.is_empty() {
+            current.push(c);
+            joined = c == '\u{200D}';
+        } else if is_emoji(c) {
+            if !joined {
+                flush(&mut current);
+            }
+            current.push(c);
+            joined = false;
+        } else {
+            flush(&mut current);
+            joined = false;
+        }
+    }
+    flush(&mut current);
+    found
+}
+
+/// Check a generated draft against the output rules in [`PROMPT`].
+pub fn validate(markdown: &str) -> Vec<Violation> {
+    validate_with_emoji(markdown, &allowed_emoji(PROMPT))
+}
+
+/// Check a generated draft, accepting the heading emoji in `emoji`; see [`allowed_emoji`].
+pub fn validate_with_emoji(markdown: &str, emoji: &[String]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut in_fence = false;
+    let mut in_notes = false;
+
+    for (index, line) in markdown.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some((level, text)) = parse_heading(trimmed) {
+            if text.contains("Editor Review Notes") {
+                in_notes = true;
+                if level != 2 {
+                    violations.push(Violation {
+                        line: number,
+                        kind: ViolationKind::BadHeading,
+                        message:
+                            "the Editor Review Notes heading must be `## ✏️ Editor Review Notes`"
+                                .to_string(),
+                    });
+                }
+                continue;
+            }
+            if in_notes {
+                continue;
+            }
+            if level <= 2 && text.contains("Tech Updates") {
+                violations.push(Violation {
+                    line: number,
+                    kind: ViolationKind::TechUpdatesHeading,
+                    message: "remove the `## 💻 Tech Updates` heading; the author adds it"
+                        .to_string(),
+                });
+                continue;
+            }
+            check_heading(number, trimmed, level, text, emoji, &mut violations);
+        } else if in_notes {
+            continue;
+        } else if is_unspaced_heading(trimmed) {
+            violations.push(Violation {
+                line: number,
+                kind: ViolationKind::BadHeading,
+                message: "headings need a space after the `#`s".to_string(),
+            });
+        } else {
+            check_emoji_in_body(number, trimmed, &mut violations);
+        }
+
+        check_links(number, trimmed, &mut violations);
+        check_spelling(number, trimmed, &mut violations);
+    }
+
+    if !in_notes {
+        violations.push(Violation {
+            line: 0,
+            kind: ViolationKind::MissingEditorNotes,
+            message: "missing the trailing `## ✏️ Editor Review Notes` section".to_string(),
+        });
+    }
+
+    violations
+}
+
+/// Build the follow-up prompt asking the model to fix `violations` in its previous answer.
+pub fn correction_prompt(violations: &[Violation]) -> String {
+    let list = violations
+        .iter()
+        .map(|v| format!("- {}", v))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "Your draft does not follow the output format. Fix these issues and reply with the complete corrected draft only, with no commentary:\n\n{}",
+        list
+    )
+}
+
+/// The level and text of an ATX heading. Like CommonMark, the `#` run must be followed by a
+/// space or end the line, so `#123` at the start of a paragraph is body text.
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    if level == 0 || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some((level, rest.trim()))
+}
+
+/// A heading missing the space after its `#`s, like `###🪨 Pebble`. A single `#` before a
+/// digit or word (`#123`) is left as body text.
+fn is_unspaced_heading(line: &str) -> bool {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let Some(next) = line[level..].chars().next() else {
+        return false;
+    };
+    level > 0 && (level > 1 || is_emoji(next))
+}
+
+fn check_heading(
+    number: usize,
+    line: &str,
+    level: usize,
+    text: &str,
+    emoji: &[String],
+    violations: &mut Vec<Violation>,
+) {
+    if level < 3 {
+        violations.push(Violation {
+            line: number,
+            kind: ViolationKind::BadHeading,
+            message: format!(
+                "entries must use `###` headings, found `{}`",
+                "#".repeat(level)
+            ),
+        });
+        return;
+    }
+    if level > 3 {
+        return;
+    }
+    if !line[level..].starts_with(' ') || text.is_empty() {
+        violations.push(Violation {
+            line: number,
+            kind: ViolationKind::BadHeading,
+            message: "headings must follow `### <emoji> <Product Name> [<version>](<url>)`"
+                .to_string(),
+        });
+        return;
+    }
+
+    let first = text.split_whitespace().next().unwrap_or_default();
+    if first.chars().any(is_emoji) {
+        if !emoji.iter().any(|e| same_emoji(e, first)) {
+            violations.push(Violation {
+                line: number,
+                kind: ViolationKind::UnknownEmoji,
+                message: format!("heading emoji {} is not in the emoji map", first),
+            });
+        }
+        if text[first.len()..].trim().is_empty() {
+            violations.push(Violation {
+                line: number,
+                kind: ViolationKind::BadHeading,
+                message: "heading has an emoji but no product name".to_string(),
+            });
+        }
+    }
+}
+
+fn check_emoji_in_body(number: usize, line: &str, violations: &mut Vec<Violation>) {
+    let prose = strip_code_spans(line);
+    let mut chars = prose.chars().peekable();
+    while let Some(c) = chars.next() {
+        // Symbols such as ⌘ or ★ only count when VS16 asks for emoji presentation.
+        let emoji = is_emoji_presentation(c) || (is_emoji(c) && chars.peek() == Some(&'\u{FE0F}'));
+        // ⚠️ is allowed in body text for warning callouts and `[⚠️ link not found]` markers.
+        if emoji && c != '\u{26A0}' {
+            violations.push(Violation {
+                line: number,
+                kind: ViolationKind::EmojiInBody,
+                message: format!("emoji {} used in body text", c),
+            });
+            return;
+        }
+    }
+}
+
+fn check_links(number: usize, line: &str, violations: &mut Vec<Violation>) {
+    for (text, url) in links(&strip_code_spans(line)) {
+        let display = text.trim();
+        let mut chars = display.chars();
+        if matches!(chars.next(), Some('v' | 'V'))
+            && chars.next().is_some_and(|c| c.is_ascii_digit())
+        {
+            violations.push(Violation {
+                line: number,
+                kind: ViolationKind::VersionPrefix,
+                message: format!(
+                    "strip the `v` prefix from version `{}` in link text",
+                    display
+                ),
+            });
+        }
+        if is_placeholder_url(&url) {
+            violations.push(Violation {
+                line: number,
+                kind: ViolationKind::PlaceholderUrl,
+                message: format!(
+                    "placeholder URL `{}`; use `[⚠️ link not found]` instead",
+                    url
+                ),
+            });
+        }
+    }
+}
+
+fn check_spelling(number: usize, line: &str, violations: &mut Vec<Violation>) {
+    let prose = strip_link_targets(&strip_code_spans(line));
+    for word in prose.split(|c: char| !c.is_ascii_alphabetic()) {
+        let lower = word.to_ascii_lowercase();
+        if let Some((american, british)) = AMERICAN_SPELLINGS.iter().find(|(a, _)| *a == lower) {
+            violations.push(Violation {
+                line: number,
+                kind: ViolationKind::AmericanSpelling,
+                message: format!(
+                    "use British spelling \"{}\" instead of \"{}\"",
+                    british, american
+                ),
+            });
+        }
+    }
+}
+
+/// Markdown `[text](url)` pairs in `line`.
+pub(crate) fn links(line: &str) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let after_open = &rest[open + 1..];
+        let Some(close) = after_open.find(']') else {
+            break;
+        };
+        let text = &after_open[..close];
+        let after_close = &after_open[close + 1..];
+        if let Some(target) = after_close.strip_prefix('(') {
+            if let Some(end) = target.find(')') {
+                found.push((text.to_string(), target[..end].trim().to_string()));
+                rest = &target[end + 1..];
+                continue;
+            }
+        }
+        rest = after_open;
+    }
+    found
+}
+
+fn is_placeholder_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    // Relative and `mailto:` links are fine; only stand-in targets are placeholders.
+    if lower.is_empty() || lower == "#" || PLACEHOLDER_TARGETS.contains(&lower.as_str()) {
+        return true;
+    }
+    // `<owner>`-style slots, as a bracket pair.
+    let bracketed = lower
+        .find('<')
+        .is_some_and(|open| lower[open..].contains('>'));
+    // `...` standing in for a path segment, but not the `v1.0...v1.1` of a compare URL.
+    let elided = lower
+        .split(['/', '?', '#', '&', '='])
+        .any(|segment| segment == "..." || segment == "…");
+    bracketed
+        || elided
+        || PLACEHOLDER_MARKERS.iter().any(|m| lower.contains(m))
+        || url.contains("TODO")
+        || url.contains("TBD")
+}
+
+fn strip_code_spans(line: &str) -> String {
+    line.split('`')
+        .enumerate()
+        .filter(|(i, _)| i % 2 == 0)
+        .map(|(_, part)| part)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn strip_link_targets(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(pos) = rest.find("](") {
+        out.push_str(&rest[..=pos]);
+        let target = &rest[pos + 2..];
+        rest = match target.find(')') {
+            Some(end) => &target[end + 1..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+pub(crate) fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2300..=0x23FF | 0x2139
+    )
+}
+
+/// Characters shown as emoji without a variation selector (Unicode `Emoji_Presentation`).
+fn is_emoji_presentation(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F1E6..=0x1F1FF
+            | 0x1F201
+            | 0x1F21A
+            | 0x1F22F
+            | 0x1F232..=0x1F236
+            | 0x1F238..=0x1F23A
+            | 0x1F250..=0x1F251
+            | 0x1F300..=0x1F320
+            | 0x1F32D..=0x1F335
+            | 0x1F337..=0x1F37C
+            | 0x1F37E..=0x1F393
+            | 0x1F3A0..=0x1F3CA
+            | 0x1F3CF..=0x1F3D3
+            | 0x1F3E0..=0x1F3F0
+            | 0x1F3F4
+            | 0x1F3F8..=0x1F43E
+            | 0x1F440
+            | 0x1F442..=0x1F4FC
+            | 0x1F4FF..=0x1F53D
+            | 0x1F54B..=0x1F54E
+            | 0x1F550..=0x1F567
+            | 0x1F57A
+            | 0x1F595..=0x1F596
+            | 0x1F5A4
+            | 0x1F5FB..=0x1F64F
+            | 0x1F680..=0x1F6C5
+            | 0x1F6CC
+            | 0x1F6D0..=0x1F6D2
+            | 0x1F6D5..=0x1F6D7
+            | 0x1F6DC..=0x1F6DF
+            | 0x1F6EB..=0x1F6EC
+            | 0x1F6F4..=0x1F6FC
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F7F0
+            | 0x1F90C..=0x1F93A
+            | 0x1F93C..=0x1F945
+            | 0x1F947..=0x1F9FF
+            | 0x1FA70..=0x1FAFF
+            | 0x231A..=0x231B
+            | 0x23E9..=0x23EC
+            | 0x23F0
+            | 0x23F3
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267F
+            | 0x2693
+            | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26CE
+            | 0x26D4
+            | 0x26EA
+            | 0x26F2..=0x26F3
+            | 0x26F5
+            | 0x26FA
+            | 0x26FD
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0
+            | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+    )
+}
+
+/// Compare emoji ignoring variation selectors, which models add or drop inconsistently.
+fn same_emoji(a: &str, b: &str) -> bool {
+    let strip = |s: &str| s.chars().filter(|c| *c != '\u{FE0F}').collect::<String>();
+    strip(a) == strip(b)
+}
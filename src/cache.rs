@@ -0,0 +1,247 @@
+use anyhow::{anyhow, Context};
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::de::{deserialize_bool, deserialize_option_u64};
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct CacheConfig {
+    #[serde(rename = "cache_dir")]
+    pub dir: Option<String>,
+    #[serde(
+        rename = "cache_ttl_secs",
+        default,
+        deserialize_with = "deserialize_option_u64"
+    )]
+    pub ttl_secs: Option<u64>,
+    #[serde(
+        rename = "cache_offline",
+        default,
+        deserialize_with = "deserialize_bool"
+    )]
+    pub offline: bool,
+}
+
+/// Validators and bookkeeping stored next to each cached body.
+#[derive(Serialize, Deserialize, Debug)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// The `Content-Type` header, for decoding text bodies.
+    #[serde(default)]
+    content_type: Option<String>,
+    fetched_at: u64,
+}
+
+/// On-disk HTTP response cache keyed by URL.
+///
+/// Fresh entries (younger than the TTL) are served without touching the network; stale ones are
+/// revalidated with `If-None-Match`/`If-Modified-Since`. In offline mode only cached bodies are
+/// returned, whatever their age.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+    ttl: Duration,
+    offline: bool,
+}
+
+impl HttpCache {
+    /// Returns `None` when no cache directory is configured.
+    pub fn new(config: CacheConfig) -> anyhow::Result<Option<Self>> {
+        let Some(dir) = config.dir.filter(|v| !v.trim().is_empty()) else {
+            if config.offline {
+                return Err(anyhow!(
+                    "Offline mode requires NEWSAGENT_CACHE_DIR to be set"
+                ));
+            }
+            return Ok(None);
+        };
+        let dir = PathBuf::from(dir.trim());
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Creating cache directory {}", dir.display()))?;
+        Ok(Some(Self {
+            dir,
+            ttl: Duration::from_secs(config.ttl_secs.unwrap_or(24 * 60 * 60)),
+            offline: config.offline,
+        }))
+    }
+
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Whether `url` would be served from the cache without a request, so callers can skip
+    /// their rate limiting.
+    pub fn is_fresh(&self, url: &str) -> bool {
+        self.load(url)
+            .is_some_and(|(entry, _)| self.offline || self.is_entry_fresh(&entry))
+    }
+
+    fn is_entry_fresh(&self, entry: &CacheEntry) -> bool {
+        let age = now_secs().saturating_sub(entry.fetched_at);
+        Duration::from_secs(age) < self.ttl
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = format!("{:x}", Sha256::digest(url.as_bytes()));
+        (
+            self.dir.join(format!("{}.body", key)),
+            self.dir.join(format!("{}.json", key)),
+        )
+    }
+
+    fn load(&self, url: &str) -> Option<(CacheEntry, Vec<u8>)> {
+        let (body_path, meta_path) = self.paths(url);
+        let meta = fs::read_to_string(meta_path).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&meta).ok()?;
+        if entry.url != url {
+            return None;
+        }
+        let body = fs::read(body_path).ok()?;
+        Some((entry, body))
+    }
+
+    fn store(&self, entry: &CacheEntry, body: &[u8]) -> anyhow::Result<()> {
+        let (body_path, meta_path) = self.paths(&entry.url);
+        fs::write(&body_path, body)
+            .with_context(|| format!("Writing cache entry {}", body_path.display()))?;
+        fs::write(&meta_path, serde_json::to_vec(entry)?)
+            .with_context(|| format!("Writing cache entry {}", meta_path.display()))?;
+        Ok(())
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn header_string(
+    response: &reqwest::Response,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// Send `request` for `url` and return the response body, going through `cache` when set.
+pub async fn fetch_bytes(
+    cache: Option<&HttpCache>,
+    request: RequestBuilder,
+    url: &str,
+) -> anyhow::Result<Vec<u8>> {
+    Ok(fetch(cache, request, url).await?.0)
+}
+
+/// Like [`fetch_bytes`], but decode the body with the charset from its `Content-Type`
+/// header, falling back to UTF-8.
+pub async fn fetch_text(
+    cache: Option<&HttpCache>,
+    request: RequestBuilder,
+    url: &str,
+) -> anyhow::Result<String> {
+    let (body, content_type) = fetch(cache, request, url).await?;
+    Ok(decode_text(&body, content_type.as_deref()))
+}
+
+fn decode_text(body: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(|value| {
+            value.split(';').skip(1).find_map(|param| {
+                let (name, label) = param.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("charset")
+                    .then(|| label.trim().trim_matches('"'))
+            })
+        })
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(body);
+    text.into_owned()
+}
+
+/// The response body and its `Content-Type`.
+async fn fetch(
+    cache: Option<&HttpCache>,
+    request: RequestBuilder,
+    url: &str,
+) -> anyhow::Result<(Vec<u8>, Option<String>)> {
+    let Some(cache) = cache else {
+        let response = request
+            .send()
+            .await
+            .context("Request failed")?
+            .error_for_status()
+            .context("Request returned error status")?;
+        let content_type = header_string(&response, CONTENT_TYPE);
+        let body = response
+            .bytes()
+            .await
+            .context("Reading response body")?
+            .to_vec();
+        return Ok((body, content_type));
+    };
+
+    let cached = cache.load(url);
+    if let Some((entry, body)) = cached.as_ref() {
+        if cache.offline || cache.is_entry_fresh(entry) {
+            log::debug!("cache hit for {}", url);
+            return Ok((body.clone(), entry.content_type.clone()));
+        }
+    } else if cache.offline {
+        return Err(anyhow!("{} is not cached (offline mode)", url));
+    }
+
+    let mut request = request;
+    if let Some((entry, _)) = cached.as_ref() {
+        if let Some(etag) = entry.etag.as_deref() {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = entry.last_modified.as_deref() {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request.send().await.context("Request failed")?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some((mut entry, body)) = cached {
+            log::debug!("cache revalidated for {}", url);
+            entry.fetched_at = now_secs();
+            cache.store(&entry, &body)?;
+            return Ok((body, entry.content_type));
+        }
+    }
+
+    let response = response
+        .error_for_status()
+        .context("Request returned error status")?;
+    let entry = CacheEntry {
+        url: url.to_string(),
+        etag: header_string(&response, ETAG),
+        last_modified: header_string(&response, LAST_MODIFIED),
+        content_type: header_string(&response, CONTENT_TYPE),
+        fetched_at: now_secs(),
+    };
+    let body = response
+        .bytes()
+        .await
+        .context("Reading response body")?
+        .to_vec();
+    cache.store(&entry, &body)?;
+    Ok((body, entry.content_type))
+}
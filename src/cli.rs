@@ -0,0 +1,450 @@
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use rig::tool::Tool;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::prompt::load_prompt;
+use crate::agent::provider::{check_model, model_name};
+use crate::agent::validate::{allowed_emoji, validate_with_emoji};
+use crate::agent::Agent;
+use crate::cache::HttpCache;
+use crate::config::AppConfig;
+use crate::output::DraftWriter;
+use crate::tools::discourse::{DiscourseArgs, DiscourseTool};
+use crate::tools::discourse_recent::DiscourseRecentTool;
+use crate::tools::github::{GithubReleaseArgs, GithubReleaseTool};
+use crate::tools::glean::GleanTool;
+use crate::tools::mailing_list::MailingListTool;
+use crate::tools::manifest::{LinkClassifier, ManifestLink};
+use crate::tools::todoist::{TaskAction, TodoistConfig, TodoistTasksArgs, TodoistTasksTool};
+use crate::tools::web::{WebReadabilityArgs, WebReadabilityTool};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "newsagent",
+    version,
+    about = "Draft the Tech Updates section of a newsletter"
+)]
+pub struct Cli {
+    /// Read settings from this TOML file instead of NEWSAGENT_CONFIG_FILE or ./newsagent.toml.
+    /// Environment variables still take precedence.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Use the settings of this `[profiles.<name>]` table from the config file.
+    /// Defaults to NEWSAGENT_PROFILE or the file's top-level `profile` key.
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Serve every fetch from the response cache and never touch the network.
+    #[arg(long, global = true)]
+    pub offline: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Generate the Tech Updates draft with the configured model (default).
+    Draft {
+        /// Write the draft to this file, or to a month-stamped file in this directory,
+        /// instead of stdout. Defaults to NEWSAGENT_OUTPUT_DIR when set.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Also copy the draft into the glean directory for future style context.
+        #[arg(long)]
+        archive: bool,
+        /// Overwrite existing files.
+        #[arg(long)]
+        force: bool,
+        /// Give the model one turn to fix any output format violations.
+        /// Defaults to NEWSAGENT_VALIDATE_SELF_CORRECT.
+        #[arg(long)]
+        self_correct: bool,
+        /// Request every link in the draft and list failures under "Links to verify".
+        /// Defaults to NEWSAGENT_CHECK_LINKS.
+        #[arg(long)]
+        check_links: bool,
+        /// Fetch every task link concurrently before prompting and give the model the results.
+        /// Defaults to NEWSAGENT_PREFETCH.
+        #[arg(long)]
+        prefetch: bool,
+        /// After a successful draft, close the Todoist tasks whose links appear in it.
+        #[arg(long, group = "task_action")]
+        close_tasks: bool,
+        /// After a successful draft, move the Todoist tasks whose links appear in it
+        /// to the `Archived/<YYYY-MM>` section.
+        #[arg(long, group = "task_action")]
+        move_tasks: bool,
+        /// With --close-tasks or --move-tasks, print what would change without touching Todoist.
+        #[arg(long, requires = "task_action")]
+        dry_run: bool,
+    },
+    /// Print the rendered Todoist task list without calling the model.
+    Tasks {
+        /// Section name to filter by (case-insensitive). Defaults to the configured section.
+        #[arg(long)]
+        section: Option<String>,
+        /// Print the classified link manifest instead of the task list.
+        #[arg(long)]
+        links: bool,
+    },
+    /// Fetch a single URL through the web, GitHub or Discourse tool and print the extracted output.
+    Fetch {
+        /// The URL to fetch.
+        url: String,
+        /// Always use the web tool, even for GitHub releases and configured Discourse hosts.
+        #[arg(long)]
+        web: bool,
+    },
+    /// Validate the configuration and construct every tool without contacting any service.
+    CheckConfig,
+    /// Check an existing draft against the Tech Updates output format, accepting the heading
+    /// emoji of the configured prompt template.
+    Validate {
+        /// The markdown file to check.
+        file: PathBuf,
+    },
+}
+
+impl Cli {
+    /// Run the selected subcommand, returning the text to print on stdout.
+    pub async fn run(self, mut config: AppConfig) -> anyhow::Result<String> {
+        if self.offline {
+            config.cache.offline = true;
+        }
+        let command = self.command.unwrap_or(Command::Draft {
+            output: None,
+            archive: false,
+            force: false,
+            self_correct: false,
+            check_links: false,
+            prefetch: false,
+            close_tasks: false,
+            move_tasks: false,
+            dry_run: false,
+        });
+        match command {
+            Command::Draft {
+                output,
+                archive,
+                force,
+                self_correct,
+                check_links,
+                prefetch,
+                close_tasks,
+                move_tasks,
+                dry_run,
+            } => {
+                config.validate.self_correct |= self_correct;
+                config.links.enabled |= check_links;
+                config.prefetch.enabled |= prefetch;
+                let tidy = if close_tasks {
+                    Some(TaskAction::Close)
+                } else if move_tasks {
+                    Some(TaskAction::Move)
+                } else {
+                    None
+                };
+                draft(config, output, archive, force, tidy, dry_run).await
+            }
+            Command::Tasks { section, links } => tasks(config, section, links).await,
+            Command::Fetch { url, web } => fetch(config, url, web).await,
+            Command::CheckConfig => check_config(config),
+            Command::Validate { file } => validate_file(&config, &file),
+        }
+    }
+}
+
+async fn draft(
+    config: AppConfig,
+    output: Option<PathBuf>,
+    archive: bool,
+    force: bool,
+    tidy: Option<TaskAction>,
+    dry_run: bool,
+) -> anyhow::Result<String> {
+    let target = output.or_else(|| config.output.dir.as_ref().map(PathBuf::from));
+    let archive = archive || config.output.archive;
+    let glean_dir = PathBuf::from(&config.glean.dir);
+    let prefix = config.output.effective_prefix().to_string();
+    let todoist = config.todoist.clone();
+
+    let now = Utc::now();
+    let writer = match (target, archive) {
+        (None, false) => None,
+        (None, true) => Some(DraftWriter::new(glean_dir, &prefix)),
+        (Some(target), false) => Some(DraftWriter::new(target, &prefix)),
+        (Some(target), true) => Some(DraftWriter::new(target, &prefix).archive_to(glean_dir)),
+    }
+    .map(|writer| writer.force(force));
+    // Refuse to overwrite before paying for a model run, not after.
+    if let Some(writer) = &writer {
+        writer.check_destinations(now)?;
+    }
+
+    let agent = Agent::new(config)?;
+    // Report links no tool can read before any tokens are spent on them.
+    let links = match agent.manifest().await {
+        Ok(links) => {
+            let unsupported: Vec<_> = links.iter().filter(|l| l.tool.is_none()).collect();
+            if !unsupported.is_empty() {
+                eprintln!(
+                    "warning: {} task link(s) cannot be fetched:",
+                    unsupported.len()
+                );
+                for link in unsupported {
+                    eprintln!("  {}", manifest_line(link));
+                }
+            }
+            Some(links)
+        }
+        Err(err) => {
+            log::warn!("could not check task links: {}", err);
+            None
+        }
+    };
+    let response = agent.prompt_with_manifest(links).await?;
+
+    let Some(writer) = writer else {
+        if let Some(action) = tidy {
+            // The draft itself goes to stdout, so keep the report out of it.
+            eprint!(
+                "{}",
+                tidy_tasks(todoist, &response, action, now, dry_run).await?
+            );
+        }
+        return Ok(response);
+    };
+    let written = match writer.write(&response, now) {
+        Ok(written) => written,
+        Err(err) => {
+            // Keep the generated draft even when it cannot be saved.
+            println!("{}", response.trim_end());
+            return Err(err.into());
+        }
+    };
+
+    let mut summary = format!("Draft written to {}", written.path.display());
+    if let Some(archived) = written.archived {
+        summary.push_str(&format!("\nArchived to {}", archived.display()));
+    }
+    if let Some(action) = tidy {
+        let report = tidy_tasks(todoist, &response, action, now, dry_run).await?;
+        summary.push('\n');
+        summary.push_str(report.trim_end());
+    }
+    Ok(summary)
+}
+
+async fn tidy_tasks(
+    config: TodoistConfig,
+    draft: &str,
+    action: TaskAction,
+    now: DateTime<Utc>,
+    dry_run: bool,
+) -> anyhow::Result<String> {
+    let section = config.project_section.clone();
+    let tool = TodoistTasksTool::new(config)?;
+    let report = tool
+        .tidy_covered(
+            draft,
+            section.as_deref(),
+            action,
+            &now.format("%Y-%m").to_string(),
+            dry_run,
+        )
+        .await
+        .context("Updating Todoist tasks")?;
+    Ok(report.to_string())
+}
+
+async fn tasks(config: AppConfig, section: Option<String>, links: bool) -> anyhow::Result<String> {
+    let section = section.or_else(|| config.todoist.project_section.clone());
+    let tool = TodoistTasksTool::new(config.todoist)?;
+    if links {
+        let classifier = LinkClassifier::from_config(&config.discourse, &config.web)?;
+        let manifest = tool
+            .with_classifier(classifier)
+            .manifest(section.as_deref())
+            .await?;
+        return Ok(manifest
+            .iter()
+            .map(|link| format!("- {}", manifest_line(link)))
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+    let output = tool.call(TodoistTasksArgs { section }).await?;
+    Ok(output.markdown)
+}
+
+fn manifest_line(link: &ManifestLink) -> String {
+    match (&link.tool, &link.unsupported) {
+        (Some(tool), _) => format!("{} ({}, {})", link.url, link.kind, tool),
+        (None, reason) => format!(
+            "{} ({}, unsupported: {})",
+            link.url,
+            link.kind,
+            reason.as_deref().unwrap_or("no tool")
+        ),
+    }
+}
+
+async fn fetch(config: AppConfig, url: String, force_web: bool) -> anyhow::Result<String> {
+    let cache = HttpCache::new(config.cache.clone())?;
+    let discourse_tool = DiscourseTool::new(
+        config.discourse.clone(),
+        config.web.max_chars.unwrap_or(8000),
+    )
+    .filter(|tool| !force_web && tool.handles_url(&url))
+    .map(|tool| tool.with_cache(cache.clone()));
+
+    let output = match discourse_tool {
+        Some(tool) => {
+            let output = tool
+                .call(DiscourseArgs {
+                    url,
+                    full_thread: false,
+                })
+                .await?;
+            serde_json::to_string_pretty(&output)
+        }
+        None if !force_web && GithubReleaseTool::handles_url(&url) => {
+            let tool = GithubReleaseTool::new(config.github, config.web.max_chars.unwrap_or(8000))?
+                .with_cache(cache);
+            let output = tool.call(GithubReleaseArgs { url }).await?;
+            serde_json::to_string_pretty(&output)
+        }
+        None => {
+            let tool = WebReadabilityTool::new(config.web)?.with_cache(cache);
+            let output = tool.call(WebReadabilityArgs { url }).await?;
+            serde_json::to_string_pretty(&output)
+        }
+    };
+    output.context("Serialising fetch output")
+}
+
+fn check_config(config: AppConfig) -> anyhow::Result<String> {
+    check_model(&config)?;
+    let model = model_name(&config)?;
+    let section = config
+        .todoist
+        .project_section
+        .clone()
+        .unwrap_or_else(|| "(all)".to_string());
+    let project_id = config.todoist.project_id.clone();
+    TodoistTasksTool::new(config.todoist)?;
+
+    let glean_dir = config.glean.dir.clone();
+    let glean_filter = config
+        .glean
+        .filter
+        .clone()
+        .unwrap_or_else(|| "(none)".to_string());
+    GleanTool::new(config.glean)?;
+
+    WebReadabilityTool::new(config.web.clone())?;
+    let max_chars = config.web.max_chars.unwrap_or(8000);
+    let github_token = if config.github.token.is_some() {
+        "set"
+    } else {
+        "(none)"
+    };
+    GithubReleaseTool::new(config.github.clone(), max_chars)?;
+
+    let cache = match HttpCache::new(config.cache.clone())? {
+        Some(cache) if cache.is_offline() => format!("{} (offline)", cache.dir().display()),
+        Some(cache) => cache.dir().display().to_string(),
+        None => "(disabled)".to_string(),
+    };
+
+    let recent_days = config.discourse.effective_recent_days();
+    let discourse_sources = DiscourseRecentTool::new(config.discourse.clone())?
+        .map(|t| t.source_labels())
+        .unwrap_or_default();
+    let discourse_hosts = DiscourseTool::new(config.discourse, max_chars)
+        .map(|t| t.base_urls())
+        .unwrap_or_default();
+    let lookback_days = config.mailing_list.effective_lookback_days();
+    let mailing_lists = MailingListTool::new(config.mailing_list, max_chars)
+        .map(|t| t.list_labels())
+        .unwrap_or_default();
+
+    let prompt = config
+        .prompt
+        .template
+        .clone()
+        .unwrap_or_else(|| "(built-in)".to_string());
+    load_prompt(&config.prompt)?;
+
+    let mut report = String::from("Configuration OK\n");
+    if let Some(profile) = &config.profile {
+        report.push_str(&format!("  profile: {}\n", profile));
+    }
+    report.push_str(&format!("  llm: {} ({})\n", config.llm.provider, model));
+    report.push_str(&format!(
+        "  todoist project: {} (section: {})\n",
+        project_id, section
+    ));
+    report.push_str(&format!(
+        "  glean dir: {} (filter: {})\n",
+        glean_dir, glean_filter
+    ));
+    report.push_str(&format!("  prompt template: {}\n", prompt));
+    report.push_str(&format!(
+        "  github: {} (token: {})\n",
+        config.github.api_url, github_token
+    ));
+    report.push_str(&format!(
+        "  discourse instances: {}\n",
+        join_or_none(&discourse_hosts)
+    ));
+    if !discourse_sources.is_empty() {
+        report.push_str(&format!(
+            "  discourse recent: {} (last {} days)\n",
+            discourse_sources.join(", "),
+            recent_days
+        ));
+    }
+    report.push_str(&format!(
+        "  mailing lists: {}\n",
+        join_or_none(&mailing_lists)
+    ));
+    if !mailing_lists.is_empty() {
+        report.push_str(&format!(
+            "  mailing list lookback: {} days\n",
+            lookback_days
+        ));
+    }
+    report.push_str(&format!("  cache: {}", cache));
+    Ok(report)
+}
+
+fn validate_file(config: &AppConfig, file: &Path) -> anyhow::Result<String> {
+    let markdown =
+        fs::read_to_string(file).with_context(|| format!("Reading {}", file.display()))?;
+    let emoji = allowed_emoji(&load_prompt(&config.prompt)?);
+    let violations = validate_with_emoji(&markdown, &emoji);
+    if violations.is_empty() {
+        return Ok(format!("{}: no format violations", file.display()));
+    }
+    let list = violations
+        .iter()
+        .map(|v| format!("  {}", v))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Err(anyhow!(
+        "{}: {} format violation(s)\n{}",
+        file.display(),
+        violations.len(),
+        list
+    ))
+}
+
+fn join_or_none(values: &[String]) -> String {
+    if values.is_empty() {
+        "(none)".to_string()
+    } else {
+        values.join(", ")
+    }
+}
@@ -1,15 +1,40 @@
-use crate::tools::discourse::DiscourseConfig;
+pub(crate) mod de;
+
+use crate::agent::audit::AuditConfig;
+use crate::agent::links::LinkCheckConfig;
+use crate::agent::prefetch::PrefetchConfig;
+use crate::agent::prompt::PromptConfig;
+use crate::agent::provider::LlmConfig;
+use crate::agent::validate::ValidateConfig;
+use crate::cache::CacheConfig;
+use crate::output::OutputConfig;
+use crate::tools::discourse::{DiscourseConfig, DiscourseInstance};
+use crate::tools::github::GithubConfig;
 use crate::tools::glean::GleanConfig;
-use crate::tools::mailing_list::MailingListConfig;
+use crate::tools::mailing_list::{ArchiveFormat, MailingListConfig};
 use crate::tools::todoist::TodoistConfig;
 use crate::tools::web::WebConfig;
+use anyhow::{anyhow, Context};
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ENV_PREFIX: &str = "NEWSAGENT_";
+const DEFAULT_CONFIG_FILE: &str = "newsagent.toml";
+const PROFILE_VAR: &str = "NEWSAGENT_PROFILE";
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct AppConfig {
-    pub gemini_api_key: String,
+    pub gemini_api_key: Option<String>,
     #[serde(default = "default_gemini_model")]
     pub gemini_model: String,
+    /// Name of the config file profile the settings were taken from.
+    pub profile: Option<String>,
+
+    #[serde(flatten)]
+    pub llm: LlmConfig,
 
     #[serde(flatten)]
     pub todoist: TodoistConfig,
@@ -18,14 +43,337 @@ pub struct AppConfig {
     #[serde(flatten)]
     pub web: WebConfig,
     #[serde(flatten)]
+    pub github: GithubConfig,
+    #[serde(flatten)]
     pub discourse: DiscourseConfig,
     #[serde(flatten)]
     pub mailing_list: MailingListConfig,
+    #[serde(flatten)]
+    pub output: OutputConfig,
+    #[serde(flatten)]
+    pub cache: CacheConfig,
+    #[serde(flatten)]
+    pub audit: AuditConfig,
+    #[serde(flatten)]
+    pub validate: ValidateConfig,
+    #[serde(flatten)]
+    pub links: LinkCheckConfig,
+    #[serde(flatten)]
+    pub prompt: PromptConfig,
+    #[serde(flatten)]
+    pub prefetch: PrefetchConfig,
 }
 
 impl AppConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        Ok(envy::prefixed("NEWSAGENT_").from_env::<AppConfig>()?)
+        Ok(envy::prefixed(ENV_PREFIX).from_env::<AppConfig>()?)
+    }
+
+    /// Load configuration from `path`, `NEWSAGENT_CONFIG_FILE` or `./newsagent.toml` (the first
+    /// that is set), with `NEWSAGENT_*` environment variables taking precedence over the file.
+    /// `profile` selects a `[profiles.<name>]` table from the file, overriding `NEWSAGENT_PROFILE`.
+    /// Without any config file this is the same as [`AppConfig::from_env`].
+    pub fn load(path: Option<&Path>, profile: Option<&str>) -> anyhow::Result<Self> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var_os("NEWSAGENT_CONFIG_FILE").map(PathBuf::from))
+            .or_else(|| {
+                let default = PathBuf::from(DEFAULT_CONFIG_FILE);
+                default.exists().then_some(default)
+            });
+        let file = path.as_deref().map(ConfigFile::read).transpose()?;
+        let profile = profile.map(|p| (PROFILE_VAR.to_string(), p.to_string()));
+        Self::from_layers(file.as_ref(), env::vars().chain(profile))
+    }
+
+    /// Build the configuration from an optional file overlaid with `vars`, which use the same
+    /// `NEWSAGENT_*` names as the environment.
+    ///
+    /// When `NEWSAGENT_PROFILE` (or the file's top-level `profile` key) names a profile, that
+    /// profile's tables are merged over the rest of the file before the variables are applied.
+    pub fn from_layers(
+        file: Option<&ConfigFile>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> anyhow::Result<Self> {
+        let overrides: BTreeMap<String, String> = vars
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(ENV_PREFIX))
+            .collect();
+        let profile = overrides
+            .get(PROFILE_VAR)
+            .cloned()
+            .or_else(|| file.and_then(ConfigFile::default_profile))
+            .filter(|p| !p.trim().is_empty());
+        let selected = match (file, profile.as_deref()) {
+            (Some(file), Some(profile)) => Some(file.select_profile(profile)?),
+            (None, Some(profile)) => {
+                return Err(anyhow!(
+                    "Profile `{}` was requested but no config file was found",
+                    profile
+                ))
+            }
+            (file, None) => file.cloned(),
+        };
+        let file = selected.as_ref();
+
+        let mut merged: BTreeMap<String, String> = file
+            .map(|f| f.vars())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (format!("{}{}", ENV_PREFIX, key.to_ascii_uppercase()), value))
+            .collect();
+        merged.extend(overrides.clone());
+
+        let mut config: AppConfig = envy::prefixed(ENV_PREFIX).from_iter(merged)?;
+
+        let Some(file) = file else {
+            return Ok(config);
+        };
+        if !overrides.contains_key("NEWSAGENT_DISCOURSE_INSTANCES") {
+            if let Some(instances) = file.discourse_instances()? {
+                config.discourse.instances = instances;
+            }
+        }
+        if !overrides.contains_key("NEWSAGENT_MAILING_LISTS") {
+            if let Some(lists) = file.mailing_lists()? {
+                config.mailing_list.lists = lists.iter().map(|l| l.name.clone()).collect();
+                config.mailing_list.list_archive_formats = lists
+                    .iter()
+                    .filter_map(|l| Some((l.name.clone(), l.format?)))
+                    .collect::<HashMap<_, _>>();
+                config.mailing_list.list_base_urls = lists
+                    .into_iter()
+                    .filter_map(|l| Some((l.name, l.base_url?)))
+                    .collect::<HashMap<_, _>>();
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// A parsed `newsagent.toml`.
+///
+/// Top-level keys use the environment variable names without the prefix (`gemini_api_key`),
+/// and tables prefix their keys with the table name, so `[web] allowlist` stands for
+/// `NEWSAGENT_WEB_ALLOWLIST`. Arrays are joined with commas. Discourse instances and mailing
+/// lists may instead be given as arrays of tables to set per-entry options.
+///
+/// `[profiles.<name>]` tables have the same layout as the file itself and describe one
+/// newsletter each; the selected profile is merged over the top-level settings.
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    path: PathBuf,
+    table: toml::Table,
+}
+
+#[derive(Deserialize, Debug)]
+struct FileDiscourseInstance {
+    host: String,
+    api_key: Option<String>,
+    api_username: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FileMailingList {
+    name: String,
+    base_url: Option<String>,
+    format: Option<ArchiveFormat>,
+}
+
+impl ConfigFile {
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Reading config file {}", path.display()))?;
+        Self::parse(path, &contents)
+    }
+
+    pub fn parse(path: &Path, contents: &str) -> anyhow::Result<Self> {
+        let table = contents
+            .parse::<toml::Table>()
+            .with_context(|| format!("Parsing config file {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            table,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Names of the `[profiles.<name>]` tables, in file order.
+    pub fn profiles(&self) -> Vec<String> {
+        self.table
+            .get("profiles")
+            .and_then(toml::Value::as_table)
+            .map(|profiles| profiles.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The top-level `profile` key, used when no profile is selected on the command line or
+    /// through `NEWSAGENT_PROFILE`.
+    fn default_profile(&self) -> Option<String> {
+        self.table
+            .get("profile")
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    }
+
+    /// The file as seen by profile `name`: its tables merged over the top-level settings, with
+    /// the other profiles dropped.
+    pub fn select_profile(&self, name: &str) -> anyhow::Result<Self> {
+        let profile = self
+            .table
+            .get("profiles")
+            .and_then(toml::Value::as_table)
+            .and_then(|profiles| profiles.get(name))
+            .ok_or_else(|| {
+                let available = self.profiles();
+                anyhow!(
+                    "Unknown profile `{}` in {} (available: {})",
+                    name,
+                    self.path.display(),
+                    if available.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        available.join(", ")
+                    }
+                )
+            })?
+            .as_table()
+            .ok_or_else(|| {
+                anyhow!(
+                    "Profile `{}` in {} must be a table",
+                    name,
+                    self.path.display()
+                )
+            })?;
+
+        let mut table = self.table.clone();
+        table.remove("profiles");
+        merge_tables(&mut table, profile);
+        table.insert("profile".to_string(), toml::Value::String(name.to_string()));
+        Ok(Self {
+            path: self.path.clone(),
+            table,
+        })
+    }
+
+    /// Scalar settings flattened to lowercase environment-style keys without the prefix.
+    fn vars(&self) -> Vec<(String, String)> {
+        let mut vars = Vec::new();
+        for (key, value) in &self.table {
+            if key == "profiles" {
+                continue;
+            }
+            match value {
+                toml::Value::Table(section) => {
+                    for (inner, value) in section {
+                        if let Some(value) = scalar_string(value) {
+                            vars.push((file_key(key, inner), value));
+                        }
+                    }
+                }
+                value => {
+                    if let Some(value) = scalar_string(value) {
+                        vars.push((key.clone(), value));
+                    }
+                }
+            }
+        }
+        vars
+    }
+
+    fn discourse_instances(&self) -> anyhow::Result<Option<Vec<DiscourseInstance>>> {
+        let Some(entries) = self.table_array("discourse", "instances") else {
+            return Ok(None);
+        };
+        let instances = entries
+            .iter()
+            .map(|entry| {
+                let entry: FileDiscourseInstance = entry.clone().try_into().with_context(|| {
+                    format!("Invalid [[discourse.instances]] in {}", self.path.display())
+                })?;
+                Ok(DiscourseInstance {
+                    base_url: entry.host,
+                    api_key: entry.api_key.filter(|k| !k.trim().is_empty()),
+                    api_username: entry.api_username,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Some(instances))
+    }
+
+    fn mailing_lists(&self) -> anyhow::Result<Option<Vec<FileMailingList>>> {
+        let Some(entries) = self.table_array("mailing_list", "lists") else {
+            return Ok(None);
+        };
+        let lists = entries
+            .iter()
+            .map(|entry| {
+                entry.clone().try_into().with_context(|| {
+                    format!("Invalid [[mailing_list.lists]] in {}", self.path.display())
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Some(lists))
+    }
+
+    /// `[[section.key]]` entries, if `section.key` is an array of tables.
+    fn table_array(&self, section: &str, key: &str) -> Option<&Vec<toml::Value>> {
+        let entries = self.table.get(section)?.as_table()?.get(key)?.as_array()?;
+        entries.iter().all(toml::Value::is_table).then_some(entries)
+    }
+}
+
+/// Overlay `overlay` onto `base`, merging nested tables key by key and replacing everything else.
+fn merge_tables(base: &mut toml::Table, overlay: &toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(inner)) => {
+                merge_tables(existing, inner)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Render a TOML value the way it would be written in an environment variable, or `None` for
+/// tables and arrays of tables.
+/// The environment-style key for `[section] inner`, so env overrides replace it.
+fn file_key(section: &str, inner: &str) -> String {
+    match (section, inner) {
+        ("mailing_list", "lists") => "mailing_lists".to_string(),
+        _ => format!("{}_{}", section, inner),
+    }
+}
+
+fn scalar_string(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(expand_home(s)),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        toml::Value::Datetime(d) => Some(d.to_string()),
+        toml::Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                toml::Value::Array(_) | toml::Value::Table(_) => None,
+                item => scalar_string(item),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join(",")),
+        toml::Value::Table(_) => None,
+    }
+}
+
+/// Expand a leading `~/` to `$HOME`, as a shell would, so paths like `~/newsletter` work.
+fn expand_home(value: &str) -> String {
+    match (value.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest).to_string_lossy().into_owned(),
+        _ => value.to_string(),
     }
 }
 
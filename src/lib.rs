@@ -1,3 +1,6 @@
 pub mod agent;
+pub mod cache;
+pub mod cli;
 pub mod config;
+pub mod output;
 pub mod tools;
@@ -1,11 +1,14 @@
 use anyhow::Context;
+use clap::Parser;
 use env_logger::Env;
-use newsagent::agent::Agent;
+use newsagent::cli::Cli;
 use newsagent::config::AppConfig;
 use std::env;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
     // Parse the specified (or default) .env file
     let dotenv_path = env::var("NEWSAGENT_DOTENV_PATH").unwrap_or_else(|_| ".env".to_string());
     let dotenv_result = dotenvy::from_path(&dotenv_path);
@@ -16,10 +19,10 @@ async fn main() -> anyhow::Result<()> {
 
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
-    let config = AppConfig::from_env().context("Reading configuration")?;
-    let agent = Agent::new(config.clone())?;
-    let response = agent.prompt().await?;
+    let config = AppConfig::load(cli.config.as_deref(), cli.profile.as_deref())
+        .context("Reading configuration")?;
+    let output = cli.run(config).await?;
 
-    println!("{}", response);
+    println!("{}", output);
     Ok(())
 }
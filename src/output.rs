@@ -0,0 +1,138 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::de::deserialize_bool;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutputError {
+    #[error("Refusing to overwrite existing file: {0} (use --force to replace it)")]
+    AlreadyExists(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct OutputConfig {
+    #[serde(rename = "output_dir")]
+    pub dir: Option<String>,
+    #[serde(rename = "output_prefix")]
+    pub prefix: Option<String>,
+    #[serde(
+        rename = "output_archive",
+        default,
+        deserialize_with = "deserialize_bool"
+    )]
+    pub archive: bool,
+}
+
+impl OutputConfig {
+    pub fn effective_prefix(&self) -> &str {
+        self.prefix
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or("tech-updates")
+    }
+}
+
+/// Where a draft ended up after [`DraftWriter::write`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenDraft {
+    pub path: PathBuf,
+    pub archived: Option<PathBuf>,
+}
+
+/// Writes generated drafts to a month-stamped file, optionally copying them into the
+/// glean directory so future runs pick them up as style context.
+#[derive(Debug, Clone)]
+pub struct DraftWriter {
+    target: PathBuf,
+    prefix: String,
+    archive_dir: Option<PathBuf>,
+    force: bool,
+}
+
+impl DraftWriter {
+    /// `target` may be a directory (the file name is derived from the month) or a file path.
+    pub fn new(target: impl Into<PathBuf>, prefix: &str) -> Self {
+        Self {
+            target: target.into(),
+            prefix: prefix.to_string(),
+            archive_dir: None,
+            force: false,
+        }
+    }
+
+    pub fn archive_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.archive_dir = Some(dir.into());
+        self
+    }
+
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// The month-stamped file name, e.g. `tech-updates-2026-10.md`.
+    pub fn file_name(&self, now: DateTime<Utc>) -> String {
+        format!("{}-{}.md", self.prefix, now.format("%Y-%m"))
+    }
+
+    /// Fail with [`OutputError::AlreadyExists`] if [`DraftWriter::write`] would overwrite a
+    /// file without `force`. Run this before generating the draft so no run is wasted.
+    pub fn check_destinations(&self, now: DateTime<Utc>) -> Result<(), OutputError> {
+        let (path, archived) = self.destinations(now);
+        for dest in std::iter::once(&path).chain(archived.as_ref()) {
+            if dest.exists() && !self.force {
+                return Err(OutputError::AlreadyExists(dest.display().to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The draft path and, when archiving, the archive copy path.
+    fn destinations(&self, now: DateTime<Utc>) -> (PathBuf, Option<PathBuf>) {
+        let file_name = self.file_name(now);
+        let path = if self.target.is_dir() || self.target.extension().is_none() {
+            self.target.join(&file_name)
+        } else {
+            self.target.clone()
+        };
+        let archived = self.archive_dir.as_ref().map(|dir| {
+            let name = path
+                .file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(&file_name));
+            dir.join(name)
+        });
+        (path, archived)
+    }
+
+    pub fn write(&self, draft: &str, now: DateTime<Utc>) -> Result<WrittenDraft, OutputError> {
+        // Check every destination up front so we never leave a half-written result.
+        self.check_destinations(now)?;
+        let (path, archived) = self.destinations(now);
+
+        let contents = format!("{}\n", draft.trim_end());
+        write_file(&path, &contents)?;
+        log::info!("wrote draft to {}", path.display());
+        if let Some(dest) = archived.as_ref() {
+            write_file(dest, &contents)?;
+            log::info!("archived draft to {}", dest.display());
+        }
+
+        Ok(WrittenDraft { path, archived })
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), OutputError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Creating directory {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("Writing {}", path.display()))?;
+    Ok(())
+}
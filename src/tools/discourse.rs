@@ -2,9 +2,14 @@ use anyhow::Context;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
 use std::time::Duration;
 use url::Url;
 
+use crate::cache::{fetch_bytes, HttpCache};
+use crate::config::de::deserialize_option_u64;
+use crate::tools::html::html_to_markdown;
+
 #[derive(Debug, thiserror::Error)]
 pub enum DiscourseToolError {
     #[error("No configured Discourse instance for host: {0}")]
@@ -19,8 +24,84 @@ pub enum DiscourseToolError {
 
 #[derive(Debug, Clone)]
 pub struct DiscourseInstance {
+    /// Host (and optional port) of the instance, optionally prefixed with `http://` or
+    /// `https://` for requests that are not made on behalf of a URL.
     pub base_url: String,
     pub api_key: Option<String>,
+    /// User the API key acts as; Discourse defaults to `system` for global keys.
+    pub api_username: Option<String>,
+}
+
+impl DiscourseInstance {
+    /// `base_url` without any scheme prefix.
+    pub fn host(&self) -> &str {
+        self.base_url
+            .strip_prefix("https://")
+            .or_else(|| self.base_url.strip_prefix("http://"))
+            .unwrap_or(&self.base_url)
+            .trim_end_matches('/')
+    }
+
+    /// Scheme and host to send API requests to, defaulting to HTTPS.
+    pub fn origin(&self) -> String {
+        let scheme = if self.base_url.starts_with("http://") {
+            "http"
+        } else {
+            "https"
+        };
+        format!("{}://{}", scheme, self.host())
+    }
+
+    /// Add the API key headers to `request` when the instance has a key.
+    pub(crate) fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.api_key {
+            Some(ref api_key) => request.header("Api-Key", api_key).header(
+                "Api-Username",
+                self.api_username.as_deref().unwrap_or("system"),
+            ),
+            None => request,
+        }
+    }
+}
+
+/// A category or tag whose new topics `discourse_recent` lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscourseSource {
+    /// Instance host the source belongs to, or `None` for every configured instance.
+    pub host: Option<String>,
+    pub kind: DiscourseSourceKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscourseSourceKind {
+    Category { slug: String, id: u64 },
+    Tag(String),
+}
+
+impl DiscourseSource {
+    /// Path of the topic list JSON for this source, e.g. `/c/announcements/41.json`.
+    pub fn path(&self) -> String {
+        match &self.kind {
+            DiscourseSourceKind::Category { slug, id } => format!("/c/{}/{}.json", slug, id),
+            DiscourseSourceKind::Tag(tag) => format!("/tag/{}.json", tag),
+        }
+    }
+
+    pub fn applies_to(&self, instance: &DiscourseInstance) -> bool {
+        self.host.as_deref().is_none_or(|h| h == instance.host())
+    }
+}
+
+impl std::fmt::Display for DiscourseSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(host) = &self.host {
+            write!(f, "{} ", host)?;
+        }
+        match &self.kind {
+            DiscourseSourceKind::Category { slug, id } => write!(f, "c/{}/{}", slug, id),
+            DiscourseSourceKind::Tag(tag) => write!(f, "tag/{}", tag),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -31,6 +112,68 @@ pub struct DiscourseConfig {
         deserialize_with = "deserialize_discourse_instances"
     )]
     pub instances: Vec<DiscourseInstance>,
+
+    /// Categories and tags watched by `discourse_recent`, as comma-separated
+    /// `[host=]c/<slug>/<id>` or `[host=]tag/<name>` entries.
+    #[serde(
+        rename = "discourse_recent",
+        default,
+        deserialize_with = "deserialize_discourse_sources"
+    )]
+    pub recent: Vec<DiscourseSource>,
+
+    #[serde(
+        rename = "discourse_recent_days",
+        default,
+        deserialize_with = "deserialize_option_u64"
+    )]
+    pub recent_days: Option<u64>,
+}
+
+impl DiscourseConfig {
+    pub fn effective_recent_days(&self) -> u64 {
+        self.recent_days.unwrap_or(14)
+    }
+}
+
+fn deserialize_discourse_sources<'de, D>(deserializer: D) -> Result<Vec<DiscourseSource>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    let Some(s) = s.filter(|v| !v.trim().is_empty()) else {
+        return Ok(Vec::new());
+    };
+
+    s.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (host, path) = match entry.split_once('=') {
+                Some((host, path)) => (Some(host.trim().to_string()), path.trim()),
+                None => (None, entry),
+            };
+            let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+            let kind = match parts.as_slice() {
+                ["c", slug, id] => id
+                    .parse::<u64>()
+                    .ok()
+                    .map(|id| DiscourseSourceKind::Category {
+                        slug: slug.to_string(),
+                        id,
+                    }),
+                ["tag", tag] if !tag.is_empty() => Some(DiscourseSourceKind::Tag(tag.to_string())),
+                _ => None,
+            }
+            .ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "invalid Discourse source `{}`, expected `c/<slug>/<id>` or `tag/<name>`",
+                    entry
+                ))
+            })?;
+            Ok(DiscourseSource { host, kind })
+        })
+        .collect()
 }
 
 fn deserialize_discourse_instances<'de, D>(
@@ -60,7 +203,11 @@ where
             } else {
                 (entry.to_string(), None)
             };
-            Ok(DiscourseInstance { base_url, api_key })
+            Ok(DiscourseInstance {
+                base_url,
+                api_key,
+                api_username: None,
+            })
         })
         .collect()
 }
@@ -70,12 +217,22 @@ pub struct DiscourseTool {
     instances: Vec<DiscourseInstance>,
     max_chars: usize,
     client: reqwest::Client,
+    cache: Option<HttpCache>,
 }
 
+/// Number of post ids requested per `/t/{id}/posts.json` call, matching Discourse's page size.
+const POSTS_PER_REQUEST: usize = 20;
+
+/// Discourse's post action type id for likes in `actions_summary`.
+const LIKE_ACTION_ID: u64 = 2;
+
 #[derive(Deserialize, Debug)]
 pub struct DiscourseArgs {
     /// The Discourse topic URL to fetch.
     pub url: String,
+    /// Return the opening post and every reply instead of a single post.
+    #[serde(default)]
+    pub full_thread: bool,
 }
 
 #[derive(Serialize, Debug)]
@@ -84,27 +241,71 @@ pub struct DiscourseOutput {
     pub author: String,
     pub date: String,
     pub text: String,
+    /// Replies after the opening post, only filled in for full-thread requests.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<DiscourseReply>,
     pub source_url: String,
     pub truncated: bool,
 }
 
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DiscourseReply {
+    pub post_number: u64,
+    pub author: String,
+    pub date: String,
+    pub likes: u64,
+    pub text: String,
+}
+
 #[derive(Deserialize, Debug)]
 struct TopicResponse {
     title: String,
     post_stream: PostStream,
 }
 
+#[derive(Deserialize, Debug)]
+struct PostsResponse {
+    post_stream: PostStream,
+}
+
 #[derive(Deserialize, Debug)]
 struct PostStream {
     posts: Vec<Post>,
+    /// Ids of every post in the topic; `posts` only holds the first page of them.
+    #[serde(default)]
+    stream: Vec<u64>,
 }
 
 #[derive(Deserialize, Debug)]
 struct Post {
+    #[serde(default)]
+    id: u64,
     post_number: u64,
     username: String,
     created_at: String,
     cooked: String,
+    like_count: Option<u64>,
+    #[serde(default)]
+    actions_summary: Vec<ActionSummary>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ActionSummary {
+    id: u64,
+    #[serde(default)]
+    count: u64,
+}
+
+impl Post {
+    fn likes(&self) -> u64 {
+        self.like_count.unwrap_or_else(|| {
+            self.actions_summary
+                .iter()
+                .find(|a| a.id == LIKE_ACTION_ID)
+                .map(|a| a.count)
+                .unwrap_or_default()
+        })
+    }
 }
 
 impl Tool for DiscourseTool {
@@ -126,6 +327,10 @@ impl Tool for DiscourseTool {
                     "url": {
                         "type": "string",
                         "description": "The Discourse topic URL to fetch."
+                    },
+                    "full_thread": {
+                        "type": "boolean",
+                        "description": "Return the opening post plus every reply (author, date, likes), e.g. to catch follow-up corrections on announcements. Defaults to false, which returns only the linked post."
                     }
                 },
                 "required": ["url"]
@@ -145,25 +350,16 @@ impl Tool for DiscourseTool {
         let (topic_id, post_number) = Self::parse_topic_url(&url)
             .ok_or_else(|| DiscourseToolError::NotATopicUrl(args.url.clone()))?;
 
-        let scheme = url.scheme();
-        let api_url = format!("{}://{}/t/{}.json", scheme, instance.base_url, topic_id);
-        let mut request = self.client.get(&api_url);
-        if let Some(ref api_key) = instance.api_key {
-            request = request
-                .header("Api-Key", api_key)
-                .header("Api-Username", "system");
-        }
-        let response = request
-            .send()
-            .await
-            .context("Discourse API request failed")?
-            .error_for_status()
-            .context("Discourse API returned error status")?;
+        let base = format!("{}://{}", url.scheme(), instance.host());
+        let topic: TopicResponse = self
+            .get_json(instance, &format!("{}/t/{}.json", base, topic_id))
+            .await?;
 
-        let topic: TopicResponse = response
-            .json()
-            .await
-            .context("Failed to parse Discourse API response")?;
+        if args.full_thread {
+            return self
+                .full_thread(instance, &base, topic_id, topic, args.url)
+                .await;
+        }
 
         let post = if let Some(num) = post_number {
             topic
@@ -177,7 +373,7 @@ impl Tool for DiscourseTool {
         }
         .ok_or_else(|| anyhow::anyhow!("No posts found in topic"))?;
 
-        let mut text = strip_html(&post.cooked);
+        let mut text = html_to_markdown(&post.cooked, Url::parse(&base).ok().as_ref());
         let truncated = if text.chars().count() > self.max_chars {
             text = text.chars().take(self.max_chars).collect::<String>();
             true
@@ -190,6 +386,7 @@ impl Tool for DiscourseTool {
             author: post.username.clone(),
             date: post.created_at.clone(),
             text,
+            replies: Vec::new(),
             source_url: args.url,
             truncated,
         })
@@ -212,21 +409,132 @@ impl DiscourseTool {
             instances: config.instances,
             max_chars,
             client,
+            cache: None,
         })
     }
 
+    /// Route API requests through an on-disk response cache.
+    pub fn with_cache(mut self, cache: Option<HttpCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
     pub fn base_urls(&self) -> Vec<String> {
-        self.instances.iter().map(|i| i.base_url.clone()).collect()
+        self.instances
+            .iter()
+            .map(|i| i.host().to_string())
+            .collect()
+    }
+
+    /// Whether `url` points at one of the configured Discourse instances.
+    pub fn handles_url(&self, url: &str) -> bool {
+        Url::parse(url)
+            .ok()
+            .is_some_and(|url| self.find_instance(&url).is_some())
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        instance: &DiscourseInstance,
+        api_url: &str,
+    ) -> Result<T, DiscourseToolError> {
+        let request = instance.authorize(self.client.get(api_url));
+        let body = fetch_bytes(self.cache.as_ref(), request, api_url)
+            .await
+            .context("Discourse API request failed")?;
+        Ok(serde_json::from_slice(&body).context("Failed to parse Discourse API response")?)
+    }
+
+    /// Collect every post of a topic, fetching the ids missing from the first page through
+    /// `/t/{id}/posts.json`, and spread `max_chars` across the opening post and its replies.
+    async fn full_thread(
+        &self,
+        instance: &DiscourseInstance,
+        base: &str,
+        topic_id: u64,
+        topic: TopicResponse,
+        source_url: String,
+    ) -> Result<DiscourseOutput, DiscourseToolError> {
+        let mut posts = topic.post_stream.posts;
+        let loaded: HashSet<u64> = posts.iter().map(|p| p.id).collect();
+        let missing: Vec<u64> = topic
+            .post_stream
+            .stream
+            .iter()
+            .copied()
+            .filter(|id| !loaded.contains(id))
+            .collect();
+
+        let mut budget = self.max_chars;
+        let first = posts
+            .iter()
+            .position(|p| p.post_number == 1)
+            .map(|i| posts.remove(i))
+            .or_else(|| (!posts.is_empty()).then(|| posts.remove(0)))
+            .ok_or_else(|| anyhow::anyhow!("No posts found in topic"))?;
+        let base_url = Url::parse(base).ok();
+        let markdown = |post: &Post| html_to_markdown(&post.cooked, base_url.as_ref());
+        let (text, mut truncated) = take_chars(markdown(&first), &mut budget);
+
+        let mut replies = Vec::new();
+        let mut pending = missing.chunks(POSTS_PER_REQUEST);
+        loop {
+            posts.sort_by_key(|p| p.post_number);
+            for post in posts.drain(..) {
+                if budget == 0 {
+                    truncated = true;
+                    break;
+                }
+                let (text, cut) = take_chars(markdown(&post), &mut budget);
+                truncated |= cut;
+                replies.push(DiscourseReply {
+                    post_number: post.post_number,
+                    author: post.username.clone(),
+                    date: post.created_at.clone(),
+                    likes: post.likes(),
+                    text,
+                });
+            }
+            if budget == 0 {
+                truncated |= pending.len() > 0;
+                break;
+            }
+            let Some(ids) = pending.next() else {
+                break;
+            };
+            let query = ids
+                .iter()
+                .map(|id| format!("post_ids[]={}", id))
+                .collect::<Vec<_>>()
+                .join("&");
+            let page: PostsResponse = self
+                .get_json(
+                    instance,
+                    &format!("{}/t/{}/posts.json?{}", base, topic_id, query),
+                )
+                .await?;
+            posts = page.post_stream.posts;
+        }
+
+        Ok(DiscourseOutput {
+            title: topic.title,
+            author: first.username,
+            date: first.created_at,
+            text,
+            replies,
+            source_url,
+            truncated,
+        })
     }
 
     fn find_instance(&self, url: &Url) -> Option<&DiscourseInstance> {
         let host = url.host_str()?;
         self.instances.iter().find(|i| {
-            if let Some((cfg_host, cfg_port)) = i.base_url.rsplit_once(':') {
+            if let Some((cfg_host, cfg_port)) = i.host().rsplit_once(':') {
                 // base_url has an explicit port — match host and port
                 host == cfg_host && url.port().map(|p| p.to_string()).as_deref() == Some(cfg_port)
             } else {
-                host == i.base_url
+                host == i.host()
             }
         })
     }
@@ -245,22 +553,15 @@ impl DiscourseTool {
     }
 }
 
-/// Simple HTML tag stripper. Replaces tags with nothing and decodes basic entities.
-fn strip_html(html: &str) -> String {
-    let mut out = String::with_capacity(html.len());
-    let mut in_tag = false;
-    for ch in html.chars() {
-        match ch {
-            '<' => in_tag = true,
-            '>' => in_tag = false,
-            _ if !in_tag => out.push(ch),
-            _ => {}
-        }
+/// Cut `text` down to the characters left in `budget`, returning whether anything was dropped.
+fn take_chars(text: String, budget: &mut usize) -> (String, bool) {
+    let count = text.chars().count();
+    if count <= *budget {
+        *budget -= count;
+        (text, false)
+    } else {
+        let text = text.chars().take(*budget).collect();
+        *budget = 0;
+        (text, true)
     }
-    out.replace("&amp;", "&")
-        .replace("&lt;", "<")
-        .replace("&gt;", ">")
-        .replace("&quot;", "\"")
-        .replace("&#39;", "'")
-        .replace("&nbsp;", " ")
 }
@@ -0,0 +1,301 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rig::completion::ToolDefinition;
+use rig::tool::Tool;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::cache::{fetch_bytes, HttpCache};
+use crate::tools::discourse::{DiscourseConfig, DiscourseInstance, DiscourseSource};
+
+/// Upper bound on topic list pages read per source, so a busy category cannot stall a run.
+const MAX_PAGES: usize = 5;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DiscourseRecentToolError {
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscourseRecentTool {
+    instances: Vec<DiscourseInstance>,
+    sources: Vec<DiscourseSource>,
+    days: u64,
+    client: reqwest::Client,
+    cache: Option<HttpCache>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DiscourseRecentArgs {}
+
+#[derive(Serialize, Debug)]
+pub struct DiscourseRecentOutput {
+    pub topics: Vec<RecentTopic>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RecentTopic {
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    pub created_at: String,
+    pub reply_count: u64,
+    pub views: u64,
+    /// The categories and tags the topic was found through.
+    pub sources: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TopicListResponse {
+    #[serde(default)]
+    users: Vec<User>,
+    topic_list: TopicList,
+}
+
+#[derive(Deserialize, Debug)]
+struct User {
+    id: i64,
+    username: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TopicList {
+    #[serde(default)]
+    topics: Vec<Topic>,
+    more_topics_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Topic {
+    id: u64,
+    title: String,
+    slug: String,
+    created_at: String,
+    #[serde(default)]
+    reply_count: u64,
+    #[serde(default)]
+    views: u64,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    posters: Vec<Poster>,
+    last_poster_username: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Poster {
+    user_id: i64,
+    #[serde(default)]
+    description: String,
+}
+
+impl Tool for DiscourseRecentTool {
+    const NAME: &'static str = "discourse_recent";
+
+    type Error = DiscourseRecentToolError;
+    type Args = DiscourseRecentArgs;
+    type Output = DiscourseRecentOutput;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: format!(
+                "List topics created in the last {} days in the watched Discourse categories and tags, newest first, with title, URL, author, reply count and views. Use discourse_fetch to read any topic worth covering. This tool takes no arguments.",
+                self.days
+            ),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {}
+            }),
+        }
+    }
+
+    async fn call(&self, _args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let cutoff = Utc::now() - chrono::Duration::days(self.days as i64);
+        let mut topics: Vec<(DateTime<Utc>, RecentTopic)> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        for instance in &self.instances {
+            for source in self.sources.iter().filter(|s| s.applies_to(instance)) {
+                let found = match self.fetch_source(instance, source, cutoff).await {
+                    Ok(found) => found,
+                    Err(e) => {
+                        log::warn!("{} {}: skipping ({})", instance.host(), source, e);
+                        continue;
+                    }
+                };
+                log::info!(
+                    "{} {}: {} recent topics",
+                    instance.host(),
+                    source,
+                    found.len()
+                );
+                let label = source_label(instance, source);
+                for (created_at, topic) in found {
+                    match index.get(&topic.url) {
+                        Some(&i) => {
+                            if !topics[i].1.sources.contains(&label) {
+                                topics[i].1.sources.push(label.clone());
+                            }
+                        }
+                        None => {
+                            index.insert(topic.url.clone(), topics.len());
+                            topics.push((
+                                created_at,
+                                RecentTopic {
+                                    sources: vec![label.clone()],
+                                    ..topic
+                                },
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        topics.sort_by_key(|(created_at, _)| std::cmp::Reverse(*created_at));
+        Ok(DiscourseRecentOutput {
+            topics: topics.into_iter().map(|(_, t)| t).collect(),
+        })
+    }
+}
+
+impl DiscourseRecentTool {
+    /// Build the tool, or `None` when no instance has a watched category or tag.
+    pub fn new(config: DiscourseConfig) -> Result<Option<Self>, DiscourseRecentToolError> {
+        let days = config.effective_recent_days();
+        let instances: Vec<DiscourseInstance> = config
+            .instances
+            .into_iter()
+            .filter(|i| config.recent.iter().any(|s| s.applies_to(i)))
+            .collect();
+        if instances.is_empty() {
+            return Ok(None);
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .user_agent("newsagent/0.1")
+            .build()
+            .context("Failed to build Discourse HTTP client")?;
+
+        Ok(Some(Self {
+            instances,
+            sources: config.recent,
+            days,
+            client,
+            cache: None,
+        }))
+    }
+
+    /// Route API requests through an on-disk response cache.
+    pub fn with_cache(mut self, cache: Option<HttpCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Watched sources per instance, e.g. `discourse.ubuntu.com c/announcements/41`.
+    pub fn source_labels(&self) -> Vec<String> {
+        self.instances
+            .iter()
+            .flat_map(|i| {
+                self.sources
+                    .iter()
+                    .filter(|s| s.applies_to(i))
+                    .map(|s| source_label(i, s))
+            })
+            .collect()
+    }
+
+    /// Topics created after `cutoff` in `source`, reading further pages while they are still
+    /// within range.
+    async fn fetch_source(
+        &self,
+        instance: &DiscourseInstance,
+        source: &DiscourseSource,
+        cutoff: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<(DateTime<Utc>, RecentTopic)>> {
+        let origin = instance.origin();
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+
+        for page in 0..MAX_PAGES {
+            let url = format!(
+                "{}{}?order=created&ascending=false&page={}",
+                origin,
+                source.path(),
+                page
+            );
+            let request = instance.authorize(self.client.get(&url));
+            let body = fetch_bytes(self.cache.as_ref(), request, &url)
+                .await
+                .context("Discourse API request failed")?;
+            let list: TopicListResponse =
+                serde_json::from_slice(&body).context("Failed to parse Discourse topic list")?;
+
+            let users: HashMap<i64, &str> = list
+                .users
+                .iter()
+                .map(|u| (u.id, u.username.as_str()))
+                .collect();
+
+            let mut reached_cutoff = list.topic_list.topics.is_empty();
+            for topic in &list.topic_list.topics {
+                let Ok(created_at) = DateTime::parse_from_rfc3339(&topic.created_at) else {
+                    log::debug!("topic {}: unparseable date {}", topic.id, topic.created_at);
+                    continue;
+                };
+                let created_at = created_at.with_timezone(&Utc);
+                if created_at < cutoff {
+                    // Pinned topics keep their place at the top regardless of age.
+                    reached_cutoff |= !topic.pinned;
+                    continue;
+                }
+                if !seen.insert(topic.id) {
+                    continue;
+                }
+                found.push((
+                    created_at,
+                    RecentTopic {
+                        title: topic.title.clone(),
+                        url: format!("{}/t/{}/{}", origin, topic.slug, topic.id),
+                        author: original_poster(topic, &users),
+                        created_at: topic.created_at.clone(),
+                        reply_count: topic.reply_count,
+                        views: topic.views,
+                        sources: Vec::new(),
+                    },
+                ));
+            }
+
+            if reached_cutoff || list.topic_list.more_topics_url.is_none() {
+                break;
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+fn source_label(instance: &DiscourseInstance, source: &DiscourseSource) -> String {
+    DiscourseSource {
+        host: Some(instance.host().to_string()),
+        kind: source.kind.clone(),
+    }
+    .to_string()
+}
+
+/// Username of the topic's original poster, falling back to its last poster.
+fn original_poster(topic: &Topic, users: &HashMap<i64, &str>) -> String {
+    topic
+        .posters
+        .iter()
+        .find(|p| p.description.contains("Original Poster"))
+        .or_else(|| topic.posters.first())
+        .and_then(|p| users.get(&p.user_id))
+        .map(|u| u.to_string())
+        .or_else(|| topic.last_poster_username.clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,306 @@
+use anyhow::Context;
+use rig::completion::ToolDefinition;
+use rig::tool::Tool;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+use crate::cache::{fetch_bytes, HttpCache};
+
+#[derive(Debug, thiserror::Error)]
+pub enum GithubReleaseToolError {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+    #[error("Not a GitHub release URL: {0}")]
+    NotAReleaseUrl(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GithubConfig {
+    #[serde(rename = "github_token")]
+    pub token: Option<String>,
+    #[serde(rename = "github_api_url", default = "default_github_api_url")]
+    pub api_url: String,
+}
+
+impl Default for GithubConfig {
+    fn default() -> Self {
+        Self {
+            token: None,
+            api_url: default_github_api_url(),
+        }
+    }
+}
+
+fn default_github_api_url() -> String {
+    "https://api.github.com".to_string()
+}
+
+#[derive(Debug, Clone)]
+pub struct GithubReleaseTool {
+    token: Option<String>,
+    api_url: String,
+    max_chars: usize,
+    client: reqwest::Client,
+    cache: Option<HttpCache>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GithubReleaseArgs {
+    /// A `github.com/<owner>/<repo>/releases/tag/<tag>` URL.
+    pub url: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GithubReleaseOutput {
+    pub repository: String,
+    pub tag: String,
+    pub name: String,
+    pub published_at: Option<String>,
+    pub author: String,
+    pub body: String,
+    pub pull_requests: Vec<u64>,
+    pub source_url: String,
+    pub truncated: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReleaseResponse {
+    tag_name: String,
+    name: Option<String>,
+    published_at: Option<String>,
+    author: Option<ReleaseAuthor>,
+    body: Option<String>,
+    html_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReleaseAuthor {
+    login: String,
+}
+
+/// The parts of a release URL needed to query the API.
+#[derive(Debug, PartialEq, Eq)]
+struct ReleaseRef {
+    owner: String,
+    repo: String,
+    tag: String,
+}
+
+impl Tool for GithubReleaseTool {
+    const NAME: &'static str = "github_release";
+
+    type Error = GithubReleaseToolError;
+    type Args = GithubReleaseArgs;
+    type Output = GithubReleaseOutput;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description:
+                "Fetch a GitHub release via the REST API and return its tag, name, published date, author, markdown body and linked PR numbers. ALWAYS prefer this over browse_web for github.com/<owner>/<repo>/releases/tag/<tag> URLs — the rendered page often loses the PR list."
+                    .to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "The GitHub release URL, e.g. https://github.com/canonical/pebble/releases/tag/v1.27.0."
+                    }
+                },
+                "required": ["url"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        log::info!("fetching github release {}...", args.url);
+        let url = Url::parse(&args.url)
+            .map_err(|_| GithubReleaseToolError::InvalidUrl(args.url.clone()))?;
+        let release_ref = parse_release_url(&url)
+            .ok_or_else(|| GithubReleaseToolError::NotAReleaseUrl(args.url.clone()))?;
+
+        let api_url = format!(
+            "{}/repos/{}/{}/releases/tags/{}",
+            self.api_url.trim_end_matches('/'),
+            release_ref.owner,
+            release_ref.repo,
+            release_ref.tag.replace('/', "%2F")
+        );
+        let mut request = self
+            .client
+            .get(&api_url)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28");
+        if let Some(ref token) = self.token {
+            request = request.bearer_auth(token);
+        }
+        let body = fetch_bytes(self.cache.as_ref(), request, &api_url)
+            .await
+            .context("GitHub API request failed")?;
+        let release: ReleaseResponse =
+            serde_json::from_slice(&body).context("Failed to parse GitHub release response")?;
+
+        let mut text = release.body.unwrap_or_default();
+        let pull_requests = linked_pull_requests(&text, &release_ref.owner, &release_ref.repo);
+        let truncated = if text.chars().count() > self.max_chars {
+            text = text.chars().take(self.max_chars).collect::<String>();
+            true
+        } else {
+            false
+        };
+
+        Ok(GithubReleaseOutput {
+            repository: format!("{}/{}", release_ref.owner, release_ref.repo),
+            name: release
+                .name
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| release.tag_name.clone()),
+            tag: release.tag_name,
+            published_at: release.published_at,
+            author: release.author.map(|a| a.login).unwrap_or_default(),
+            body: text,
+            pull_requests,
+            source_url: release.html_url.unwrap_or(args.url),
+            truncated,
+        })
+    }
+}
+
+impl GithubReleaseTool {
+    pub fn new(config: GithubConfig, max_chars: usize) -> Result<Self, GithubReleaseToolError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .user_agent("newsagent/0.1")
+            .build()
+            .context("Failed to build GitHub HTTP client")?;
+
+        Ok(Self {
+            token: config.token.filter(|t| !t.trim().is_empty()),
+            api_url: config.api_url,
+            max_chars,
+            client,
+            cache: None,
+        })
+    }
+
+    /// Route API requests through an on-disk response cache.
+    pub fn with_cache(mut self, cache: Option<HttpCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Whether `url` is a GitHub release tag URL this tool can fetch.
+    pub fn handles_url(url: &str) -> bool {
+        Url::parse(url)
+            .ok()
+            .is_some_and(|url| parse_release_url(&url).is_some())
+    }
+}
+
+/// Parse `https://github.com/<owner>/<repo>/releases/tag/<tag>`. Tags may contain slashes.
+fn parse_release_url(url: &Url) -> Option<ReleaseRef> {
+    let host = url.host_str()?;
+    if host != "github.com" && host != "www.github.com" {
+        return None;
+    }
+    let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+    if segments.len() < 5 || segments[2] != "releases" || segments[3] != "tag" {
+        return None;
+    }
+    Some(ReleaseRef {
+        owner: segments[0].to_string(),
+        repo: segments[1].to_string(),
+        tag: segments[4..].join("/"),
+    })
+}
+
+/// Collect PR numbers referenced as `#123` or as full pull request URLs for this repository,
+/// in order of first appearance.
+fn linked_pull_requests(body: &str, owner: &str, repo: &str) -> Vec<u64> {
+    let mut numbers: Vec<u64> = Vec::new();
+    let mut push = |n: u64| {
+        if !numbers.contains(&n) {
+            numbers.push(n);
+        }
+    };
+
+    let pull_prefix = format!("github.com/{}/{}/pull/", owner, repo).to_ascii_lowercase();
+    let lower = body.to_ascii_lowercase();
+    let bytes = body.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if lower.as_bytes()[i..].starts_with(pull_prefix.as_bytes()) {
+            let start = i + pull_prefix.len();
+            let digits = leading_digits(&body[start..]);
+            if let Ok(n) = digits.parse() {
+                push(n);
+            }
+            i = start + digits.len();
+            continue;
+        }
+        if bytes[i] == b'#' {
+            let preceded_by_word = i > 0
+                && (bytes[i - 1].is_ascii_alphanumeric() || matches!(bytes[i - 1], b'&' | b'/'));
+            let digits = leading_digits(&body[i + 1..]);
+            if !preceded_by_word {
+                if let Ok(n) = digits.parse() {
+                    push(n);
+                }
+            }
+            i += 1 + digits.len();
+            continue;
+        }
+        i += 1;
+    }
+    numbers
+}
+
+fn leading_digits(s: &str) -> &str {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    &s[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_release_urls() {
+        let url = Url::parse("https://github.com/canonical/charmcraft/releases/tag/4.1.0").unwrap();
+        assert_eq!(
+            parse_release_url(&url),
+            Some(ReleaseRef {
+                owner: "canonical".to_string(),
+                repo: "charmcraft".to_string(),
+                tag: "4.1.0".to_string(),
+            })
+        );
+
+        let nested = Url::parse("https://github.com/juju/juju/releases/tag/charm/v1.0").unwrap();
+        assert_eq!(parse_release_url(&nested).unwrap().tag, "charm/v1.0");
+
+        for other in [
+            "https://github.com/juju/juju/releases",
+            "https://github.com/juju/juju/pull/1",
+            "https://example.com/juju/juju/releases/tag/v1",
+        ] {
+            assert_eq!(parse_release_url(&Url::parse(other).unwrap()), None);
+        }
+    }
+
+    #[test]
+    fn extracts_linked_pull_requests() {
+        let body = "## What's Changed\n\
+            * Add syslog target by @a in https://github.com/canonical/pebble/pull/567\n\
+            * Fix race (#571), see also #567\n\
+            * Upstream fix in https://github.com/other/repo/pull/9\n\
+            * Anchor link [x](https://example.com/page#12) and entity &#39;\n";
+        assert_eq!(
+            linked_pull_requests(body, "canonical", "pebble"),
+            vec![567, 571]
+        );
+    }
+}
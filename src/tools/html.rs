@@ -0,0 +1,513 @@
+use html5ever::tendril::TendrilSink;
+use html5ever::{parse_document, ParseOpts};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use serde::Serialize;
+use std::collections::HashMap;
+use url::Url;
+
+/// A hyperlink found in a page, with its anchor text.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub text: String,
+    pub url: String,
+}
+
+/// Elements whose content never reaches the reader.
+const SKIPPED: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "button", "form",
+];
+
+/// Elements that start a new block; everything else is rendered inline.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "details",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Convert an HTML fragment or document to markdown, keeping links, code blocks, lists,
+/// headings, quotes and tables. Relative links and images are resolved against `base`.
+pub fn html_to_markdown(html: &str, base: Option<&Url>) -> String {
+    let dom = parse_document(RcDom::default(), ParseOpts::default()).one(html);
+    let renderer = Renderer { base };
+    let markdown = renderer.blocks(&dom.document).join("\n\n");
+    tidy(&markdown)
+}
+
+/// Every `http(s)` link in `html`, resolved against `base` and deduplicated by URL. The first
+/// non-empty anchor text wins; links without any text are labelled with their `title` or URL.
+pub fn extract_links(html: &str, base: Option<&Url>) -> Vec<Link> {
+    let dom = parse_document(RcDom::default(), ParseOpts::default()).one(html);
+    let renderer = Renderer { base };
+    let mut links: Vec<Link> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    renderer.collect_links(&dom.document, &mut |url, text| match index.get(&url) {
+        Some(&i) => {
+            if links[i].text.is_empty() {
+                links[i].text = text;
+            }
+        }
+        None => {
+            index.insert(url.clone(), links.len());
+            links.push(Link { text, url });
+        }
+    });
+    for link in &mut links {
+        if link.text.is_empty() {
+            link.text = link.url.clone();
+        }
+    }
+    links
+}
+
+struct Renderer<'a> {
+    base: Option<&'a Url>,
+}
+
+impl Renderer<'_> {
+    /// Render the children of `node` as a list of markdown blocks.
+    fn blocks(&self, node: &Handle) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut inline = String::new();
+        for child in node.children.borrow().iter() {
+            match element_name(child) {
+                Some(name) if SKIPPED.contains(&name.as_str()) => {}
+                Some(name) if BLOCKS.contains(&name.as_str()) => {
+                    push_paragraph(&mut blocks, &mut inline);
+                    blocks.extend(self.block(child, &name));
+                }
+                _ => inline.push_str(&self.inline(child)),
+            }
+        }
+        push_paragraph(&mut blocks, &mut inline);
+        blocks
+    }
+
+    fn block(&self, node: &Handle, name: &str) -> Vec<String> {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = collapse(&self.inline_children(node));
+                if text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![format!("{} {}", "#".repeat(level), text)]
+                }
+            }
+            "p" | "dt" | "summary" | "figcaption" => {
+                let text = collapse(&self.inline_children(node));
+                if text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![text]
+                }
+            }
+            "pre" => vec![self.code_block(node)],
+            "hr" => vec!["---".to_string()],
+            "blockquote" => {
+                let inner = self.blocks(node).join("\n\n");
+                if inner.is_empty() {
+                    return Vec::new();
+                }
+                vec![inner
+                    .lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {}", line)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")]
+            }
+            "ul" | "ol" => {
+                let list = self.list(node, name == "ol");
+                if list.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![list]
+                }
+            }
+            "table" => {
+                let table = self.table(node);
+                if table.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![table]
+                }
+            }
+            _ => self.blocks(node),
+        }
+    }
+
+    fn list(&self, node: &Handle, ordered: bool) -> String {
+        let start = attr(node, "start")
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(1);
+        let mut items = Vec::new();
+        for child in node.children.borrow().iter() {
+            if element_name(child).as_deref() != Some("li") {
+                continue;
+            }
+            let marker = if ordered {
+                format!("{}. ", start + items.len())
+            } else {
+                "- ".to_string()
+            };
+            let indent = " ".repeat(marker.len());
+            let body = self.blocks(child).join("\n");
+            let mut lines = body.lines();
+            let mut item = format!("{}{}", marker, lines.next().unwrap_or_default());
+            for line in lines {
+                item.push('\n');
+                if !line.is_empty() {
+                    item.push_str(&indent);
+                    item.push_str(line);
+                }
+            }
+            items.push(item.trim_end().to_string());
+        }
+        items.join("\n")
+    }
+
+    fn code_block(&self, node: &Handle) -> String {
+        let language = find_element(node, "code")
+            .and_then(|code| attr(&code, "class"))
+            .and_then(|class| {
+                class.split_whitespace().find_map(|c| {
+                    c.strip_prefix("lang-")
+                        .or_else(|| c.strip_prefix("language-"))
+                        .map(str::to_string)
+                })
+            })
+            .filter(|lang| lang != "auto" && lang != "nohighlight")
+            .unwrap_or_default();
+        let mut code = String::new();
+        raw_text(node, &mut code);
+        let fence = if code.contains("```") { "~~~" } else { "```" };
+        format!(
+            "{}{}\n{}\n{}",
+            fence,
+            language,
+            code.trim_matches('\n'),
+            fence
+        )
+    }
+
+    fn table(&self, node: &Handle) -> String {
+        let mut rows: Vec<(Vec<String>, bool)> = Vec::new();
+        collect_rows(node, false, &mut |row, in_head| {
+            let mut cells = Vec::new();
+            let mut header = in_head;
+            for cell in row.children.borrow().iter() {
+                match element_name(cell).as_deref() {
+                    Some("th") => {
+                        header = true;
+                        cells.push(collapse(&self.inline_children(cell)).replace('|', "\\|"));
+                    }
+                    Some("td") => {
+                        cells.push(collapse(&self.inline_children(cell)).replace('|', "\\|"))
+                    }
+                    _ => {}
+                }
+            }
+            if !cells.is_empty() {
+                rows.push((cells, header));
+            }
+        });
+        let Some(width) = rows.iter().map(|(cells, _)| cells.len()).max() else {
+            return String::new();
+        };
+
+        let mut lines = Vec::new();
+        for (index, (mut cells, header)) in rows.into_iter().enumerate() {
+            cells.resize(width, String::new());
+            lines.push(format!("| {} |", cells.join(" | ")));
+            if index == 0 && header {
+                lines.push(format!("|{}", " --- |".repeat(width)));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn inline_children(&self, node: &Handle) -> String {
+        node.children
+            .borrow()
+            .iter()
+            .map(|child| self.inline(child))
+            .collect()
+    }
+
+    fn inline(&self, node: &Handle) -> String {
+        match &node.data {
+            NodeData::Text { contents } => whitespace_to_space(&contents.borrow()),
+            NodeData::Element { .. } => {
+                let name = element_name(node).unwrap_or_default();
+                match name.as_str() {
+                    n if SKIPPED.contains(&n) => String::new(),
+                    "br" => "\n".to_string(),
+                    "a" => self.link(node),
+                    "img" => self.image(node),
+                    "code" | "kbd" | "samp" | "tt" => {
+                        let mut code = String::new();
+                        raw_text(node, &mut code);
+                        let code = whitespace_to_space(&code);
+                        let code = code.trim();
+                        if code.is_empty() {
+                            String::new()
+                        } else if code.contains('`') {
+                            format!("`` {} ``", code)
+                        } else {
+                            format!("`{}`", code)
+                        }
+                    }
+                    "strong" | "b" => wrap(&self.inline_children(node), "**"),
+                    "em" | "i" => wrap(&self.inline_children(node), "*"),
+                    "del" | "s" | "strike" => wrap(&self.inline_children(node), "~~"),
+                    // Block elements nested in inline context (e.g. a `<div>` inside `<a>`).
+                    n if BLOCKS.contains(&n) => {
+                        format!(" {} ", self.blocks(node).join(" "))
+                    }
+                    _ => self.inline_children(node),
+                }
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn link(&self, node: &Handle) -> String {
+        let text = collapse(&self.inline_children(node));
+        let Some(href) = attr(node, "href").and_then(|h| self.resolve(&h)) else {
+            return text;
+        };
+        if text.is_empty() {
+            format!("<{}>", href)
+        } else {
+            format!("[{}]({})", text, href)
+        }
+    }
+
+    fn image(&self, node: &Handle) -> String {
+        let alt = attr(node, "alt").unwrap_or_default();
+        match attr(node, "src").and_then(|src| self.resolve(&src)) {
+            Some(src) => format!("![{}]({})", collapse(&alt), src),
+            None => String::new(),
+        }
+    }
+
+    fn collect_links(&self, node: &Handle, found: &mut dyn FnMut(String, String)) {
+        for child in node.children.borrow().iter() {
+            match element_name(child).as_deref() {
+                Some(name) if SKIPPED.contains(&name) => {}
+                Some("a") => {
+                    let url = attr(child, "href")
+                        .and_then(|h| self.resolve(&h))
+                        .filter(|u| u.starts_with("http://") || u.starts_with("https://"));
+                    if let Some(url) = url {
+                        let mut text = String::new();
+                        raw_text(child, &mut text);
+                        let text = collapse(&whitespace_to_space(&text));
+                        let text = if text.is_empty() {
+                            attr(child, "title")
+                                .map(|t| collapse(&t))
+                                .unwrap_or_default()
+                        } else {
+                            text
+                        };
+                        found(url, text);
+                    }
+                }
+                _ => self.collect_links(child, found),
+            }
+        }
+    }
+
+    /// Resolve `href` against the base URL, dropping in-page anchors and script links.
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty()
+            || href.starts_with('#')
+            || href.to_ascii_lowercase().starts_with("javascript:")
+        {
+            return None;
+        }
+        match self.base {
+            Some(base) => base.join(href).ok().map(|url| url.to_string()),
+            None => Some(href.to_string()),
+        }
+    }
+}
+
+fn push_paragraph(blocks: &mut Vec<String>, inline: &mut String) {
+    let text = collapse(inline);
+    if !text.is_empty() {
+        blocks.push(text);
+    }
+    inline.clear();
+}
+
+fn collect_rows(node: &Handle, in_head: bool, f: &mut dyn FnMut(&Handle, bool)) {
+    for child in node.children.borrow().iter() {
+        match element_name(child).as_deref() {
+            Some("tr") => f(child, in_head),
+            Some("thead") => collect_rows(child, true, f),
+            Some("tbody" | "tfoot") => collect_rows(child, false, f),
+            _ => {}
+        }
+    }
+}
+
+fn element_name(node: &Handle) -> Option<String> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(name.local.to_string()),
+        _ => None,
+    }
+}
+
+fn attr(node: &Handle, key: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|a| &*a.name.local == key)
+            .map(|a| a.value.to_string()),
+        _ => None,
+    }
+}
+
+fn find_element(node: &Handle, name: &str) -> Option<Handle> {
+    for child in node.children.borrow().iter() {
+        if element_name(child).as_deref() == Some(name) {
+            return Some(child.clone());
+        }
+        if let Some(found) = find_element(child, name) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Append the text content of `node` verbatim, keeping line breaks from `<br>`.
+fn raw_text(node: &Handle, out: &mut String) {
+    for child in node.children.borrow().iter() {
+        match &child.data {
+            NodeData::Text { contents } => out.push_str(&contents.borrow()),
+            NodeData::Element { .. } if element_name(child).as_deref() == Some("br") => {
+                out.push('\n')
+            }
+            NodeData::Element { .. } => raw_text(child, out),
+            _ => {}
+        }
+    }
+}
+
+fn whitespace_to_space(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_whitespace() && c != '\u{a0}' {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Collapse runs of spaces within each line and trim the result, keeping `<br>` line breaks.
+fn collapse(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            line.split(' ')
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .replace('\u{a0}', " ")
+}
+
+/// Wrap inline content in an emphasis marker, keeping surrounding spaces outside it.
+fn wrap(content: &str, marker: &str) -> String {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return content.to_string();
+    }
+    let leading = if content.starts_with(' ') { " " } else { "" };
+    let trailing = if content.ends_with(' ') { " " } else { "" };
+    format!("{}{}{}{}{}", leading, marker, trimmed, marker, trailing)
+}
+
+/// Drop trailing spaces and squeeze runs of blank lines outside code blocks. Lines inside
+/// a fenced block are kept as they are until the fence that opened it closes it.
+fn tidy(markdown: &str) -> String {
+    let mut out = Vec::new();
+    let mut blank = 0;
+    // The marker that opened the current code block; only the same marker closes it.
+    let mut fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+                out.push(line.trim_end());
+            } else {
+                out.push(line);
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            blank = 0;
+            out.push(line.trim_end());
+            continue;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank += 1;
+            if blank > 1 {
+                continue;
+            }
+        } else {
+            blank = 0;
+        }
+        out.push(line);
+    }
+    out.join("\n").trim().to_string()
+}
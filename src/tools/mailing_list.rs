@@ -1,15 +1,26 @@
 use anyhow::Context;
-use chrono::{Datelike, NaiveDateTime, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use flate2::read::GzDecoder;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::io::Read as IoRead;
+use std::str::FromStr;
 use std::time::Duration;
 
+use crate::cache::{fetch_bytes, HttpCache};
+use crate::config::de::{deserialize_option_u64, deserialize_option_usize};
+
+/// How many lines of each reply make it into the thread outline.
+const REPLY_EXCERPT_LINES: usize = 6;
+
 #[derive(Debug, thiserror::Error)]
 pub enum MailingListToolError {
+    #[error("invalid `{field}` date `{value}`, expected YYYY-MM-DD")]
+    InvalidDate { field: &'static str, value: String },
+    #[error("`since` ({since}) is after `until` ({until})")]
+    EmptyRange { since: String, until: String },
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -25,6 +36,149 @@ pub struct MailingListConfig {
 
     #[serde(rename = "mailing_list_base_url", default)]
     pub base_url: Option<String>,
+
+    /// How many days back to look when the model passes no `since` date.
+    #[serde(
+        rename = "mailing_list_lookback_days",
+        default,
+        deserialize_with = "deserialize_option_u64"
+    )]
+    pub lookback_days: Option<u64>,
+
+    /// How many threads to return when the model passes no `limit`.
+    #[serde(
+        rename = "mailing_list_max_threads",
+        default,
+        deserialize_with = "deserialize_option_usize"
+    )]
+    pub max_threads: Option<usize>,
+
+    /// Per-list archive base URLs, keyed by list name. Only settable from the config file.
+    #[serde(skip)]
+    pub list_base_urls: HashMap<String, String>,
+
+    /// Archive format for lists without their own. Pipermail unless set.
+    #[serde(
+        rename = "mailing_list_archive_format",
+        default,
+        deserialize_with = "deserialize_option_archive_format"
+    )]
+    pub archive_format: Option<ArchiveFormat>,
+
+    /// Per-list archive formats, keyed by list name. Only settable from the config file.
+    #[serde(skip)]
+    pub list_archive_formats: HashMap<String, ArchiveFormat>,
+}
+
+/// How a list archive is laid out on its host.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(try_from = "String")]
+pub enum ArchiveFormat {
+    /// Mailman 2 Pipermail, as on lists.ubuntu.com: one `{base}/{list}/{YYYY-Month}.txt.gz`
+    /// per month.
+    #[default]
+    Pipermail,
+    /// Mailman 3 Hyperkitty: one `{base}/list/{list}/export/{name}.mbox.gz?start=..&end=..`
+    /// for the whole window. `base` is the `/archives` root and `list` the list address.
+    Hyperkitty,
+    /// public-inbox: the gzipped mbox of a date search, `POST {base}/{list}/?q=d:..&x=m`.
+    PublicInbox,
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "pipermail" | "mailman2" => Ok(ArchiveFormat::Pipermail),
+            "hyperkitty" | "mailman3" => Ok(ArchiveFormat::Hyperkitty),
+            "public_inbox" | "publicinbox" => Ok(ArchiveFormat::PublicInbox),
+            _ => Err(format!(
+                "unknown archive format `{}`, expected pipermail, hyperkitty or public-inbox",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for ArchiveFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl std::fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ArchiveFormat::Pipermail => "pipermail",
+            ArchiveFormat::Hyperkitty => "hyperkitty",
+            ArchiveFormat::PublicInbox => "public-inbox",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One archive download for a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArchiveRequest {
+    /// What to call the download in logs, e.g. `2025-December`.
+    label: String,
+    url: String,
+    post: bool,
+}
+
+impl ArchiveFormat {
+    /// The downloads covering `since` to `last_day`, both inclusive, for `list` at `base_url`.
+    fn requests(
+        self,
+        base_url: &str,
+        list: &str,
+        since: NaiveDate,
+        last_day: NaiveDate,
+    ) -> Vec<ArchiveRequest> {
+        let end = last_day + chrono::Duration::days(1);
+        match self {
+            ArchiveFormat::Pipermail => month_strings(since, last_day)
+                .into_iter()
+                .map(|month| ArchiveRequest {
+                    url: format!("{}/{}/{}.txt.gz", base_url, list, month),
+                    label: month,
+                    post: false,
+                })
+                .collect(),
+            ArchiveFormat::Hyperkitty => {
+                let label = format!("{}..{}", since, last_day);
+                vec![ArchiveRequest {
+                    url: format!(
+                        "{base}/list/{list}/export/{list}-{since}-{last_day}.mbox.gz?start={since}&end={end}",
+                        base = base_url,
+                        list = list,
+                        since = since,
+                        last_day = last_day,
+                        end = end,
+                    ),
+                    label,
+                    post: false,
+                }]
+            }
+            ArchiveFormat::PublicInbox => {
+                let label = format!("{}..{}", since, last_day);
+                vec![ArchiveRequest {
+                    url: format!(
+                        "{}/{}/?q=d%3A{}..{}&x=m",
+                        base_url,
+                        list,
+                        since.format("%Y%m%d"),
+                        end.format("%Y%m%d")
+                    ),
+                    label,
+                    post: true,
+                }]
+            }
+        }
+    }
 }
 
 impl MailingListConfig {
@@ -33,6 +187,43 @@ impl MailingListConfig {
             .as_deref()
             .unwrap_or("https://lists.ubuntu.com/archives")
     }
+
+    pub fn effective_lookback_days(&self) -> u64 {
+        self.lookback_days.filter(|d| *d > 0).unwrap_or(30)
+    }
+
+    pub fn effective_max_threads(&self) -> usize {
+        self.max_threads.filter(|n| *n > 0).unwrap_or(20)
+    }
+
+    /// The base URL and archive format `list` is read from.
+    pub fn archive_for(&self, list: &str) -> (&str, ArchiveFormat) {
+        let base_url = self
+            .list_base_urls
+            .get(list)
+            .map(String::as_str)
+            .unwrap_or_else(|| self.effective_base_url());
+        let format = self
+            .list_archive_formats
+            .get(list)
+            .copied()
+            .or(self.archive_format)
+            .unwrap_or_default();
+        (base_url, format)
+    }
+}
+
+fn deserialize_option_archive_format<'de, D>(
+    deserializer: D,
+) -> Result<Option<ArchiveFormat>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s.filter(|v| !v.trim().is_empty()) {
+        Some(s) => s.parse().map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
 }
 
 fn deserialize_comma_separated<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
@@ -53,17 +244,54 @@ where
 #[derive(Debug, Clone)]
 pub struct MailingListTool {
     lists: Vec<String>,
-    base_url: String,
+    /// Base URL and archive format per list.
+    archives: HashMap<String, (String, ArchiveFormat)>,
+    lookback_days: u64,
+    max_threads: usize,
     client: reqwest::Client,
     max_chars: usize,
+    cache: Option<HttpCache>,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct MailingListArgs {}
+#[derive(Deserialize, Debug, Default)]
+pub struct MailingListArgs {
+    /// First day to include, as `YYYY-MM-DD`. Defaults to the configured lookback before `until`.
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Last day to include, as `YYYY-MM-DD`. Defaults to now.
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Keep threads whose subject or any message body contains one of these words
+    /// (case-insensitive).
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Keep threads with a message from an author whose name contains one of these.
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// Only read lists matching one of these names; `*` matches any run of characters.
+    #[serde(default)]
+    pub lists: Vec<String>,
+    /// Skip lists matching one of these names; `*` matches any run of characters.
+    #[serde(default)]
+    pub exclude_lists: Vec<String>,
+    /// Drop threads with fewer messages than this.
+    #[serde(default)]
+    pub min_messages: Option<usize>,
+    /// Return at most this many threads. Defaults to the configured maximum.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
 
 #[derive(Serialize, Debug)]
 pub struct MailingListOutput {
+    /// First day covered, `YYYY-MM-DD`.
+    pub since: String,
+    /// Last day covered, `YYYY-MM-DD`.
+    pub until: String,
+    /// Highest scoring threads first.
     pub threads: Vec<ThreadSummary>,
+    /// Threads that matched the filters but were cut by the limit.
+    pub omitted: usize,
 }
 
 #[derive(Serialize, Debug)]
@@ -74,8 +302,25 @@ pub struct ThreadSummary {
     pub authors: Vec<String>,
     pub first_date: String,
     pub last_date: String,
+    /// The opening message, with quoted text and signature removed.
     pub summary: String,
+    /// The replies in date order, each cut to its first few lines.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<ReplyExcerpt>,
+    /// Replies left out because the thread ran out of `max_chars`.
+    pub omitted_replies: usize,
+    /// Whether the summary or any reply was cut short.
     pub truncated: bool,
+    /// Activity score: messages, plus two per distinct author, plus up to five for how late in
+    /// the window the last message landed.
+    pub score: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReplyExcerpt {
+    pub author: String,
+    pub date: String,
+    pub excerpt: String,
 }
 
 #[derive(Debug, Clone)]
@@ -85,7 +330,7 @@ struct ParsedMessage {
     references: Vec<String>,
     subject: String,
     from: String,
-    date: Option<NaiveDateTime>,
+    date: Option<DateTime<FixedOffset>>,
     body: String,
     list_name: String,
 }
@@ -100,37 +345,84 @@ impl Tool for MailingListTool {
     async fn definition(&self, _prompt: String) -> ToolDefinition {
         ToolDefinition {
             name: Self::NAME.to_string(),
-            description:
-                "Fetch recent discussion threads from configured Ubuntu mailing lists. Returns deduplicated threads from the last 30 days across all lists. This tool takes no arguments."
-                    .to_string(),
+            description: format!(
+                "Fetch discussion threads from configured Ubuntu mailing lists. Returns deduplicated threads across all lists from the last {} days, or from `since` to `until` when given, ranked by an activity score (messages, distinct authors, recency) and capped at {} threads. Optional filters narrow the result by keyword, author, list and thread size.",
+                self.lookback_days, self.max_threads
+            ),
             parameters: serde_json::json!({
                 "type": "object",
-                "properties": {}
+                "properties": {
+                    "since": {
+                        "type": "string",
+                        "description": format!("First day to include, as YYYY-MM-DD. Defaults to {} days before `until`.", self.lookback_days)
+                    },
+                    "until": {
+                        "type": "string",
+                        "description": "Last day to include, as YYYY-MM-DD. Defaults to today."
+                    },
+                    "keywords": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Keep threads whose subject or messages mention any of these words (case-insensitive)."
+                    },
+                    "authors": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Keep threads with a message from an author whose name contains any of these."
+                    },
+                    "lists": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": format!("Only read these lists (`*` wildcards allowed). Configured: {}.", self.lists.join(", "))
+                    },
+                    "exclude_lists": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Skip these lists (`*` wildcards allowed)."
+                    },
+                    "min_messages": {
+                        "type": "integer",
+                        "description": "Drop threads with fewer messages than this."
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": format!("Return at most this many threads. Defaults to {}.", self.max_threads)
+                    }
+                }
             }),
         }
     }
 
-    async fn call(&self, _args: Self::Args) -> Result<Self::Output, Self::Error> {
-        let cutoff = Utc::now().naive_utc() - chrono::Duration::days(30);
-        let months = month_strings(Utc::now());
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let (since, until) = resolve_window(&args, self.lookback_days, Utc::now().naive_utc())?;
+        let last_day = (until - chrono::Duration::seconds(1)).date();
+
+        let lists: Vec<&String> = self
+            .lists
+            .iter()
+            .filter(|name| {
+                args.lists.is_empty() || args.lists.iter().any(|p| matches_pattern(p, name))
+            })
+            .filter(|name| !args.exclude_lists.iter().any(|p| matches_pattern(p, name)))
+            .collect();
 
         let mut all_messages = Vec::new();
 
-        for list_name in &self.lists {
-            for month in &months {
-                match self.fetch_month(list_name, month).await {
+        for list_name in lists {
+            for request in self.archive_requests(list_name, since.date(), last_day) {
+                match self.fetch_archive(&request).await {
                     Ok(data) => {
-                        let messages = parse_mbox(&data, list_name, cutoff);
+                        let messages = parse_mbox(&data, list_name, since, until);
                         log::info!(
                             "{}/{}: {} messages in range",
                             list_name,
-                            month,
+                            request.label,
                             messages.len()
                         );
                         all_messages.extend(messages);
                     }
                     Err(e) => {
-                        log::warn!("{}/{}: skipping ({})", list_name, month, e);
+                        log::warn!("{}/{}: skipping ({})", list_name, request.label, e);
                     }
                 }
             }
@@ -141,12 +433,25 @@ impl Tool for MailingListTool {
 
         let mut summaries: Vec<ThreadSummary> = deduplicated
             .into_iter()
-            .map(|t| self.thread_to_summary(t))
+            .filter(|t| thread_matches(t, &args))
+            .map(|t| self.thread_to_summary(t, since, until))
             .collect();
 
-        summaries.sort_by(|a, b| b.last_date.cmp(&a.last_date));
-
-        Ok(MailingListOutput { threads: summaries })
+        summaries.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.last_date.cmp(&a.last_date))
+        });
+        let limit = args.limit.filter(|n| *n > 0).unwrap_or(self.max_threads);
+        let omitted = summaries.len().saturating_sub(limit);
+        summaries.truncate(limit);
+
+        Ok(MailingListOutput {
+            since: since.date().format("%Y-%m-%d").to_string(),
+            until: last_day.format("%Y-%m-%d").to_string(),
+            threads: summaries,
+            omitted,
+        })
     }
 }
 
@@ -156,10 +461,19 @@ impl MailingListTool {
             return None;
         }
 
-        let base_url = config
-            .effective_base_url()
-            .trim_end_matches('/')
-            .to_string();
+        let lookback_days = config.effective_lookback_days();
+        let max_threads = config.effective_max_threads();
+        let archives = config
+            .lists
+            .iter()
+            .map(|name| {
+                let (base_url, format) = config.archive_for(name);
+                (
+                    name.clone(),
+                    (base_url.trim_end_matches('/').to_string(), format),
+                )
+            })
+            .collect();
 
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(30))
@@ -169,31 +483,60 @@ impl MailingListTool {
 
         Some(Self {
             lists: config.lists,
-            base_url,
+            archives,
+            lookback_days,
+            max_threads,
             client,
             max_chars,
+            cache: None,
         })
     }
 
+    /// Route archive downloads through an on-disk response cache.
+    pub fn with_cache(mut self, cache: Option<HttpCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
     pub fn list_names(&self) -> &[String] {
         &self.lists
     }
 
-    async fn fetch_month(&self, list_name: &str, month: &str) -> anyhow::Result<Vec<u8>> {
-        let url = format!("{}/{}/{}.txt.gz", self.base_url, list_name, month);
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Mailing list archive request failed")?
-            .error_for_status()
-            .context("Mailing list archive returned error status")?;
+    /// The list names, each followed by its archive format unless that is Pipermail.
+    pub fn list_labels(&self) -> Vec<String> {
+        self.lists
+            .iter()
+            .map(|name| match self.archives.get(name) {
+                Some((_, format)) if *format != ArchiveFormat::Pipermail => {
+                    format!("{} ({})", name, format)
+                }
+                _ => name.clone(),
+            })
+            .collect()
+    }
+
+    /// The downloads covering `since` to `last_day` for `list_name`, in its archive format.
+    fn archive_requests(
+        &self,
+        list_name: &str,
+        since: NaiveDate,
+        last_day: NaiveDate,
+    ) -> Vec<ArchiveRequest> {
+        let Some((base_url, format)) = self.archives.get(list_name) else {
+            return Vec::new();
+        };
+        format.requests(base_url, list_name, since, last_day)
+    }
 
-        let compressed = response
-            .bytes()
+    async fn fetch_archive(&self, request: &ArchiveRequest) -> anyhow::Result<Vec<u8>> {
+        let builder = if request.post {
+            self.client.post(&request.url)
+        } else {
+            self.client.get(&request.url)
+        };
+        let compressed = fetch_bytes(self.cache.as_ref(), builder, &request.url)
             .await
-            .context("Failed to read archive response body")?;
+            .context("Mailing list archive request failed")?;
 
         let mut decoder = GzDecoder::new(&compressed[..]);
         let mut decompressed = Vec::new();
@@ -204,7 +547,12 @@ impl MailingListTool {
         Ok(decompressed)
     }
 
-    fn thread_to_summary(&self, thread: Thread) -> ThreadSummary {
+    fn thread_to_summary(
+        &self,
+        thread: Thread,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> ThreadSummary {
         let mut authors: Vec<String> = thread
             .messages
             .iter()
@@ -228,31 +576,57 @@ impl MailingListTool {
             .iter()
             .filter_map(|m| m.date)
             .min()
-            .map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string())
+            .map(format_utc)
             .unwrap_or_default();
 
-        let last_date = thread
-            .messages
-            .iter()
-            .filter_map(|m| m.date)
-            .max()
-            .map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string())
-            .unwrap_or_default();
+        let last = thread.messages.iter().filter_map(|m| m.date).max();
+        let last_date = last.map(format_utc).unwrap_or_default();
 
-        let first_body = thread
-            .messages
-            .iter()
-            .min_by_key(|m| m.date)
-            .map(|m| &m.body)
-            .cloned()
-            .unwrap_or_default();
+        let window = (until - since).num_seconds().max(1) as f64;
+        let recency = last
+            .map(|d| ((d.naive_utc() - since).num_seconds() as f64 / window).clamp(0.0, 1.0))
+            .unwrap_or(0.0);
+        let score = thread.messages.len() as f64 + 2.0 * authors.len() as f64 + 5.0 * recency;
 
-        let truncated = first_body.chars().count() > self.max_chars;
-        let summary = if truncated {
-            first_body.chars().take(self.max_chars).collect()
+        let mut ordered: Vec<&ParsedMessage> = thread.messages.iter().collect();
+        ordered.sort_by_key(|m| m.date);
+
+        // The opening message and the replies share `max_chars`; when there are replies the
+        // opening message may use at most half of it.
+        let mut budget = self.max_chars;
+        let first_body = ordered
+            .first()
+            .map(|m| strip_quotes_and_signature(&m.body))
+            .unwrap_or_default();
+        let summary_budget = if ordered.len() > 1 {
+            budget / 2
         } else {
-            first_body
+            budget
         };
+        let (summary, mut truncated) = take_chars(&first_body, summary_budget);
+        budget -= summary.chars().count();
+
+        let mut replies = Vec::new();
+        let mut omitted_replies = 0;
+        for message in ordered.iter().skip(1) {
+            let excerpt = reply_excerpt(&message.body, REPLY_EXCERPT_LINES);
+            if excerpt.is_empty() {
+                continue;
+            }
+            if budget == 0 {
+                omitted_replies += 1;
+                truncated = true;
+                continue;
+            }
+            let (excerpt, cut) = take_chars(&excerpt, budget);
+            budget -= excerpt.chars().count();
+            truncated |= cut;
+            replies.push(ReplyExcerpt {
+                author: message.from.clone(),
+                date: message.date.map(format_utc).unwrap_or_default(),
+                excerpt,
+            });
+        }
 
         ThreadSummary {
             subject: thread.subject,
@@ -262,87 +636,318 @@ impl MailingListTool {
             first_date,
             last_date,
             summary,
+            replies,
+            omitted_replies,
             truncated,
+            score: (score * 100.0).round() / 100.0,
         }
     }
 }
 
-/// Generate the Mailman month directory names for the current and previous month.
-/// E.g. for March 2026: ["2026-March", "2026-February"]
-fn month_strings(now: chrono::DateTime<Utc>) -> Vec<String> {
-    let month_names = [
-        "January",
-        "February",
-        "March",
-        "April",
-        "May",
-        "June",
-        "July",
-        "August",
-        "September",
-        "October",
-        "November",
-        "December",
-    ];
-
-    let current = format!("{}-{}", now.year(), month_names[now.month0() as usize]);
-
-    let (prev_year, prev_month0) = if now.month0() == 0 {
-        (now.year() - 1, 11usize)
-    } else {
-        (now.year(), (now.month0() - 1) as usize)
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Generate the Mailman month directory names for every month from `since` to `until`,
+/// newest first. E.g. for 2026-01-20..2026-03-05: ["2026-March", "2026-February", "2026-January"]
+fn month_strings(since: NaiveDate, until: NaiveDate) -> Vec<String> {
+    let mut months = Vec::new();
+    let (mut year, mut month0) = (until.year(), until.month0());
+    while (year, month0) >= (since.year(), since.month0()) {
+        months.push(format!("{}-{}", year, MONTH_NAMES[month0 as usize]));
+        if month0 == 0 {
+            year -= 1;
+            month0 = 11;
+        } else {
+            month0 -= 1;
+        }
+    }
+    months
+}
+
+/// Resolve the tool arguments to a `[since, until)` window. `until` defaults to `now` and
+/// `since` to `lookback_days` before `until`; explicit dates cover whole days.
+fn resolve_window(
+    args: &MailingListArgs,
+    lookback_days: u64,
+    now: NaiveDateTime,
+) -> Result<(NaiveDateTime, NaiveDateTime), MailingListToolError> {
+    let until = match non_empty(&args.until) {
+        Some(value) => parse_day(value, "until")?
+            .succ_opt()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .ok_or_else(|| MailingListToolError::InvalidDate {
+                field: "until",
+                value: value.to_string(),
+            })?,
+        None => now,
+    };
+    let since = match non_empty(&args.since) {
+        Some(value) => parse_day(value, "since")?.and_time(chrono::NaiveTime::MIN),
+        None => until - chrono::Duration::days(lookback_days as i64),
     };
-    let previous = format!("{}-{}", prev_year, month_names[prev_month0]);
+    if since >= until {
+        return Err(MailingListToolError::EmptyRange {
+            since: since.date().format("%Y-%m-%d").to_string(),
+            until: (until - chrono::Duration::seconds(1))
+                .date()
+                .format("%Y-%m-%d")
+                .to_string(),
+        });
+    }
+    Ok((since, until))
+}
 
-    vec![current, previous]
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
 }
 
-/// Split raw mbox data into individual messages and parse them.
-/// Filters to messages within the cutoff date.
-fn parse_mbox(data: &[u8], list_name: &str, cutoff: NaiveDateTime) -> Vec<ParsedMessage> {
-    let text = String::from_utf8_lossy(data);
-    let mut messages = Vec::new();
+fn parse_day(value: &str, field: &'static str) -> Result<NaiveDate, MailingListToolError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| MailingListToolError::InvalidDate {
+        field,
+        value: value.to_string(),
+    })
+}
 
-    // Split on mbox "From " separator lines
-    let mut current_message = String::new();
-    for line in text.lines() {
-        if line.starts_with("From ") && !current_message.is_empty() {
-            if let Some(msg) = parse_single_message(&current_message, list_name, cutoff) {
-                messages.push(msg);
-            }
-            current_message.clear();
+/// The first `budget` characters of `text`, and whether anything was cut.
+fn take_chars(text: &str, budget: usize) -> (String, bool) {
+    if text.chars().count() > budget {
+        (text.chars().take(budget).collect(), true)
+    } else {
+        (text.to_string(), false)
+    }
+}
+
+/// The first `lines` non-blank lines of a reply, without quotes or signature.
+fn reply_excerpt(body: &str, lines: usize) -> String {
+    strip_quotes_and_signature(body)
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(lines)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Drop quoted lines (`>`) and the "On ..., X wrote:" lines introducing them, cut everything
+/// from the signature delimiter, a Mailman footer or a forwarded original onwards, and
+/// collapse runs of blank lines.
+fn strip_quotes_and_signature(body: &str) -> String {
+    let lines: Vec<&str> = body.lines().collect();
+    let mut out: Vec<&str> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_end();
+        if trimmed == "--"
+            || trimmed.starts_with("-----Original Message-----")
+            || (trimmed.len() >= 10 && trimmed.chars().all(|c| c == '_'))
+        {
+            break;
         }
-        current_message.push_str(line);
-        current_message.push('\n');
+        if trimmed.trim_start().starts_with('>') {
+            continue;
+        }
+        if trimmed.ends_with("wrote:")
+            && lines[i + 1..]
+                .iter()
+                .find(|l| !l.trim().is_empty())
+                .is_some_and(|l| l.trim_start().starts_with('>'))
+        {
+            continue;
+        }
+        if trimmed.is_empty() && out.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        out.push(trimmed);
     }
-    // Don't forget the last message
-    if !current_message.is_empty() {
-        if let Some(msg) = parse_single_message(&current_message, list_name, cutoff) {
-            messages.push(msg);
+    out.join("\n").trim().to_string()
+}
+
+/// Whether `thread` passes the keyword, author and size filters in `args`.
+fn thread_matches(thread: &Thread, args: &MailingListArgs) -> bool {
+    if args
+        .min_messages
+        .is_some_and(|min| thread.messages.len() < min)
+    {
+        return false;
+    }
+    let keywords: Vec<String> = args
+        .keywords
+        .iter()
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if !keywords.is_empty() {
+        let subject = thread.subject.to_lowercase();
+        let found = keywords.iter().any(|k| {
+            subject.contains(k)
+                || thread
+                    .messages
+                    .iter()
+                    .any(|m| m.body.to_lowercase().contains(k))
+        });
+        if !found {
+            return false;
         }
     }
+    let authors: Vec<String> = args
+        .authors
+        .iter()
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| !a.is_empty())
+        .collect();
+    if !authors.is_empty() {
+        let found = thread.messages.iter().any(|m| {
+            let from = m.from.to_lowercase();
+            authors.iter().any(|a| from.contains(a))
+        });
+        if !found {
+            return false;
+        }
+    }
+    true
+}
 
+/// Match `name` against `pattern`, where `*` matches any run of characters. Case-insensitive.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let name = name.to_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Split raw mbox data into individual messages and parse them.
+/// Filters to messages dated within `[since, until)`.
+fn parse_mbox(
+    data: &[u8],
+    list_name: &str,
+    since: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Vec<ParsedMessage> {
+    split_mbox(data)
+        .iter()
+        .filter_map(|raw| parse_single_message(raw, list_name, since, until))
+        .collect()
+}
+
+/// Split an mbox archive into raw messages. A message starts at a `From ` line that opens the
+/// file or follows a blank line and carries an envelope date, so body lines starting with
+/// "From " in unescaped archives stay in their message. `>From ` lines lose one `>` (mboxrd).
+/// The bytes are otherwise untouched so each message is decoded with its own charset.
+fn split_mbox(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    let mut current = Vec::new();
+    let mut previous_blank = true;
+    for line in data.split_inclusive(|b| *b == b'\n') {
+        let content = line.strip_suffix(b"\n").unwrap_or(line);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+        if previous_blank && is_envelope_line(content) {
+            if current.iter().any(|b: &u8| !b.is_ascii_whitespace()) {
+                messages.push(std::mem::take(&mut current));
+            }
+            current.clear();
+            previous_blank = false;
+            continue;
+        }
+        previous_blank = content.is_empty();
+        let quotes = content.iter().take_while(|b| **b == b'>').count();
+        if quotes > 0 && content[quotes..].starts_with(b"From ") {
+            current.extend_from_slice(&line[1..]);
+        } else {
+            current.extend_from_slice(line);
+        }
+    }
+    if current.iter().any(|b| !b.is_ascii_whitespace()) {
+        messages.push(current);
+    }
     messages
 }
 
+/// Whether `line` is an mbox envelope line: `From <sender> <date>`, where the date has a month
+/// name, a time and a year, as in `From alice at example.com  Mon Dec  1 10:00:00 2025`.
+fn is_envelope_line(line: &[u8]) -> bool {
+    let Some(rest) = line.strip_prefix(b"From ") else {
+        return false;
+    };
+    let rest = String::from_utf8_lossy(rest);
+    let mut fields = rest.split_whitespace();
+    if fields.next().is_none() {
+        return false;
+    }
+    let fields: Vec<&str> = fields.collect();
+    let has_month = fields.iter().any(|f| {
+        MONTH_NAMES
+            .iter()
+            .any(|m| f.trim_end_matches(',') == &m[..3])
+    });
+    let has_time = fields.iter().any(|f| {
+        let parts: Vec<&str> = f.split(':').collect();
+        (2..=3).contains(&parts.len())
+            && parts
+                .iter()
+                .all(|p| (1..=2).contains(&p.len()) && p.bytes().all(|b| b.is_ascii_digit()))
+    });
+    let has_year = fields
+        .iter()
+        .any(|f| f.len() == 4 && f.bytes().all(|b| b.is_ascii_digit()));
+    has_month && has_time && has_year
+}
+
+/// The `Date` header with the sender's UTC offset.
+fn message_date(date: &mail_parser::DateTime) -> Option<DateTime<FixedOffset>> {
+    let offset_secs = (date.tz_hour as i32 * 3600 + date.tz_minute as i32 * 60)
+        * if date.tz_before_gmt { -1 } else { 1 };
+    let local = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(date.year as i32, date.month as u32, date.day as u32)?,
+        chrono::NaiveTime::from_hms_opt(date.hour as u32, date.minute as u32, date.second as u32)?,
+    );
+    FixedOffset::east_opt(offset_secs)?
+        .from_local_datetime(&local)
+        .single()
+}
+
+/// Dates in the tool output are UTC, so threads from senders in different zones compare.
+fn format_utc(date: DateTime<FixedOffset>) -> String {
+    date.naive_utc().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
 fn parse_single_message(
-    raw: &str,
+    raw: &[u8],
     list_name: &str,
-    cutoff: NaiveDateTime,
+    since: NaiveDateTime,
+    until: NaiveDateTime,
 ) -> Option<ParsedMessage> {
-    let parsed = mail_parser::MessageParser::default().parse(raw.as_bytes())?;
-
-    let date = parsed.date().and_then(|d| {
-        NaiveDateTime::new(
-            chrono::NaiveDate::from_ymd_opt(d.year as i32, d.month as u32, d.day as u32)?,
-            chrono::NaiveTime::from_hms_opt(d.hour as u32, d.minute as u32, d.second as u32)?,
-        )
-        .into()
-    });
+    let parsed = mail_parser::MessageParser::default().parse(raw)?;
+
+    let date = parsed.date().and_then(message_date);
 
     // Filter by date if we have one
     if let Some(d) = date {
-        if d < cutoff {
+        let utc = d.naive_utc();
+        if utc < since || utc >= until {
             return None;
         }
     }
@@ -549,17 +1154,226 @@ mod tests {
         assert_eq!(normalize_subject("Re: Re: Double re"), "double re");
     }
 
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
     #[test]
-    fn test_month_strings() {
-        use chrono::TimeZone;
+    fn test_strip_quotes_and_signature() {
+        let body = "On Mon, 1 Dec 2025, Alice wrote:\n> Should we ship it?\n>\n> Alice\n\nYes, but only\n\n\nafter the freeze.\n\n-- \nBob\nubuntu-devel mailing list\n";
+        assert_eq!(
+            strip_quotes_and_signature(body),
+            "Yes, but only\n\nafter the freeze."
+        );
 
-        let march = Utc.with_ymd_and_hms(2026, 3, 15, 0, 0, 0).unwrap();
-        let months = month_strings(march);
+        let body =
+            "Agreed.\n_______________________________________________\nubuntu-release mailing list";
+        assert_eq!(strip_quotes_and_signature(body), "Agreed.");
+
+        let body = "Great release, but I wrote: this is not a quote.\nThanks";
+        assert_eq!(strip_quotes_and_signature(body), body);
+    }
+
+    #[test]
+    fn test_reply_excerpt() {
+        let body = "one\n\ntwo\n> quoted\nthree\nfour";
+        assert_eq!(reply_excerpt(body, 3), "one\ntwo\nthree");
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("ubuntu-devel", "ubuntu-devel"));
+        assert!(matches_pattern("Ubuntu-*", "ubuntu-release"));
+        assert!(matches_pattern("*-devel", "ubuntu-devel"));
+        assert!(matches_pattern("*", "technical-board"));
+        assert!(matches_pattern("u*-d*l", "ubuntu-devel"));
+        assert!(!matches_pattern("ubuntu-*", "technical-board"));
+        assert!(!matches_pattern("ubuntu", "ubuntu-devel"));
+        assert!(!matches_pattern("*-devel-*", "ubuntu-devel"));
+    }
+
+    #[test]
+    fn test_split_mbox() {
+        let mbox = b"From alice at example.com  Mon Dec  1 10:00:00 2025\n\
+Subject: First\n\
+\n\
+Agenda:\n\
+\n\
+From the release team, nothing.\n\
+>From the archive, escaped.\n\
+>>From a quote, escaped twice.\n\
+\n\
+From bob@example.com Tue Dec 02 11:00:00 2025\r\n\
+Subject: Second\r\n\
+\r\n\
+caf\xe9\r\n";
+        let messages = split_mbox(mbox);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            String::from_utf8_lossy(&messages[0]),
+            "Subject: First\n\nAgenda:\n\nFrom the release team, nothing.\nFrom the archive, escaped.\n>From a quote, escaped twice.\n\n"
+        );
+        assert_eq!(messages[1], b"Subject: Second\r\n\r\ncaf\xe9\r\n".to_vec());
+    }
+
+    #[test]
+    fn test_is_envelope_line() {
+        assert!(is_envelope_line(
+            b"From alice at example.com  Mon Dec  1 10:00:00 2025"
+        ));
+        assert!(is_envelope_line(b"From mboxrd@z Thu Jan  1 00:00:00 1970"));
+        assert!(is_envelope_line(
+            b"From a@example.com Mon, 15 Dec 2025 10:00:00 +0000"
+        ));
+        assert!(!is_envelope_line(b"From the release team, nothing."));
+        assert!(!is_envelope_line(b"From 9:30 to 10:00 on 2025-12-01"));
+        assert!(!is_envelope_line(b"Subject: From Dec 1 10:00 2025"));
+    }
+
+    #[test]
+    fn test_parse_message_keeps_offset_and_charset() {
+        let raw = b"From: =?iso-8859-1?q?Ren=E9?= <rene@example.com>\n\
+Subject: Caf\xe9\n\
+Date: Mon, 01 Dec 2025 23:30:00 -0500\n\
+Content-Type: text/plain; charset=\"iso-8859-1\"\n\
+\n\
+D\xe9j\xe0 vu\n";
+        let since = day(2025, 12, 2).and_hms_opt(0, 0, 0).unwrap();
+        let until = day(2025, 12, 3).and_hms_opt(0, 0, 0).unwrap();
+        let message = parse_single_message(raw, "ubuntu-devel", since, until)
+            .expect("message is in range once converted to UTC");
+
+        assert_eq!(message.from, "René");
+        assert_eq!(message.body.trim(), "Déjà vu");
+        let date = message.date.unwrap();
+        assert_eq!(date.offset().local_minus_utc(), -5 * 3600);
+        assert_eq!(format_utc(date), "2025-12-02T04:30:00");
+
+        let until = day(2025, 12, 2).and_hms_opt(0, 0, 0).unwrap();
+        let since = day(2025, 12, 1).and_hms_opt(0, 0, 0).unwrap();
+        assert!(parse_single_message(raw, "ubuntu-devel", since, until).is_none());
+    }
+
+    #[test]
+    fn test_archive_requests() {
+        let requests = ArchiveFormat::Pipermail.requests(
+            "https://lists.ubuntu.com/archives",
+            "ubuntu-devel",
+            day(2025, 11, 20),
+            day(2025, 12, 5),
+        );
+        let urls: Vec<&str> = requests.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://lists.ubuntu.com/archives/ubuntu-devel/2025-December.txt.gz",
+                "https://lists.ubuntu.com/archives/ubuntu-devel/2025-November.txt.gz",
+            ]
+        );
+
+        let requests = ArchiveFormat::Hyperkitty.requests(
+            "https://lists.debian.org/archives",
+            "devel@lists.debian.org",
+            day(2025, 11, 20),
+            day(2025, 12, 5),
+        );
+        assert_eq!(
+            requests,
+            [ArchiveRequest {
+                label: "2025-11-20..2025-12-05".to_string(),
+                url: "https://lists.debian.org/archives/list/devel@lists.debian.org/export/devel@lists.debian.org-2025-11-20-2025-12-05.mbox.gz?start=2025-11-20&end=2025-12-06".to_string(),
+                post: false,
+            }]
+        );
+
+        let requests = ArchiveFormat::PublicInbox.requests(
+            "https://lore.kernel.org",
+            "git",
+            day(2025, 11, 20),
+            day(2025, 12, 5),
+        );
+        assert_eq!(
+            requests[0].url,
+            "https://lore.kernel.org/git/?q=d%3A20251120..20251206&x=m"
+        );
+        assert!(requests[0].post);
+    }
+
+    #[test]
+    fn test_archive_format_from_str() {
+        assert_eq!("Hyperkitty".parse(), Ok(ArchiveFormat::Hyperkitty));
+        assert_eq!("public-inbox".parse(), Ok(ArchiveFormat::PublicInbox));
+        assert_eq!("mailman2".parse(), Ok(ArchiveFormat::Pipermail));
+        assert!("mbox".parse::<ArchiveFormat>().is_err());
+    }
+
+    #[test]
+    fn test_month_strings() {
+        let months = month_strings(day(2026, 2, 13), day(2026, 3, 15));
         assert_eq!(months, vec!["2026-March", "2026-February"]);
 
-        let january = Utc.with_ymd_and_hms(2026, 1, 5, 0, 0, 0).unwrap();
-        let months = month_strings(january);
+        let months = month_strings(day(2025, 12, 6), day(2026, 1, 5));
         assert_eq!(months, vec!["2026-January", "2025-December"]);
+
+        let months = month_strings(day(2026, 3, 1), day(2026, 3, 31));
+        assert_eq!(months, vec!["2026-March"]);
+
+        let months = month_strings(day(2025, 11, 20), day(2026, 2, 10));
+        assert_eq!(
+            months,
+            vec![
+                "2026-February",
+                "2026-January",
+                "2025-December",
+                "2025-November"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_window() {
+        let now = day(2026, 3, 15).and_hms_opt(12, 0, 0).unwrap();
+
+        let (since, until) = resolve_window(&MailingListArgs::default(), 30, now).unwrap();
+        assert_eq!(until, now);
+        assert_eq!(since, day(2026, 2, 13).and_hms_opt(12, 0, 0).unwrap());
+
+        let args = MailingListArgs {
+            since: Some("2026-01-01".to_string()),
+            until: Some("2026-02-28".to_string()),
+            ..MailingListArgs::default()
+        };
+        let (since, until) = resolve_window(&args, 30, now).unwrap();
+        assert_eq!(since, day(2026, 1, 1).and_hms_opt(0, 0, 0).unwrap());
+        assert_eq!(until, day(2026, 3, 1).and_hms_opt(0, 0, 0).unwrap());
+
+        let args = MailingListArgs {
+            since: None,
+            until: Some("2026-02-28".to_string()),
+            ..MailingListArgs::default()
+        };
+        let (since, _) = resolve_window(&args, 7, now).unwrap();
+        assert_eq!(since, day(2026, 2, 22).and_hms_opt(0, 0, 0).unwrap());
+
+        let args = MailingListArgs {
+            since: Some("2026-03-02".to_string()),
+            until: Some("2026-03-01".to_string()),
+            ..MailingListArgs::default()
+        };
+        assert!(matches!(
+            resolve_window(&args, 30, now),
+            Err(MailingListToolError::EmptyRange { .. })
+        ));
+
+        let args = MailingListArgs {
+            since: Some("March 2026".to_string()),
+            until: None,
+            ..MailingListArgs::default()
+        };
+        assert!(matches!(
+            resolve_window(&args, 30, now),
+            Err(MailingListToolError::InvalidDate { field: "since", .. })
+        ));
     }
 
     #[test]
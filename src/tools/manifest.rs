@@ -0,0 +1,120 @@
+use rig::tool::Tool;
+use serde::Serialize;
+use std::fmt;
+use url::Url;
+
+use crate::tools::discourse::{DiscourseConfig, DiscourseTool};
+use crate::tools::github::GithubReleaseTool;
+use crate::tools::web::{WebConfig, WebReadabilityTool, WebReadabilityToolError};
+
+/// What a task link points at.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    GithubRelease,
+    Discourse,
+    Launchpad,
+    Docs,
+    Web,
+}
+
+impl fmt::Display for LinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LinkKind::GithubRelease => "github release",
+            LinkKind::Discourse => "discourse",
+            LinkKind::Launchpad => "launchpad",
+            LinkKind::Docs => "docs",
+            LinkKind::Web => "web",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A distinct URL found in the Todoist tasks, with the tool that should read it.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManifestLink {
+    pub url: String,
+    pub kind: LinkKind,
+    /// The tool to fetch the URL with, or `None` when no tool can.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<&'static str>,
+    /// Why the URL cannot be fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsupported: Option<String>,
+    /// Content of the tasks the URL appears in.
+    pub tasks: Vec<String>,
+}
+
+/// Decides which tool handles a URL, using the same rules as the tools themselves.
+#[derive(Debug, Clone, Default)]
+pub struct LinkClassifier {
+    discourse: Option<DiscourseTool>,
+    web: Option<WebReadabilityTool>,
+}
+
+impl LinkClassifier {
+    pub fn new(discourse: Option<DiscourseTool>, web: Option<WebReadabilityTool>) -> Self {
+        Self { discourse, web }
+    }
+
+    /// Build the classifier from configuration, without the agent's caches.
+    pub fn from_config(
+        discourse: &DiscourseConfig,
+        web: &WebConfig,
+    ) -> Result<Self, WebReadabilityToolError> {
+        Ok(Self::new(
+            DiscourseTool::new(discourse.clone(), 0),
+            Some(WebReadabilityTool::new(web.clone())?),
+        ))
+    }
+
+    pub fn classify(&self, url: &str) -> ManifestLink {
+        let mut link = ManifestLink {
+            url: url.to_string(),
+            kind: LinkKind::Web,
+            tool: None,
+            unsupported: None,
+            tasks: Vec::new(),
+        };
+        let parsed = Url::parse(url).ok();
+        let host = match parsed
+            .as_ref()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        {
+            Some(host) => host,
+            None => {
+                link.unsupported = Some("not a valid URL".to_string());
+                return link;
+            }
+        };
+
+        if GithubReleaseTool::handles_url(url) {
+            link.kind = LinkKind::GithubRelease;
+            link.tool = Some(GithubReleaseTool::NAME);
+            return link;
+        }
+        // discourse_fetch reads topics only; category and listing pages on a configured host
+        // are ordinary web pages.
+        let is_topic = parsed.as_ref().is_some_and(|u| u.path().starts_with("/t/"));
+        if is_topic && self.discourse.as_ref().is_some_and(|d| d.handles_url(url)) {
+            link.kind = LinkKind::Discourse;
+            link.tool = Some(DiscourseTool::NAME);
+            return link;
+        }
+
+        link.kind = if host == "launchpad.net" || host.ends_with(".launchpad.net") {
+            LinkKind::Launchpad
+        } else if host == "documentation.ubuntu.com" || host == "docs.ubuntu.com" {
+            LinkKind::Docs
+        } else {
+            LinkKind::Web
+        };
+        if self.web.as_ref().is_none_or(|w| w.is_host_allowed(&host)) {
+            link.tool = Some(WebReadabilityTool::NAME);
+        } else {
+            link.unsupported = Some(format!("{} is not in NEWSAGENT_WEB_ALLOWLIST", host));
+        }
+        link
+    }
+}
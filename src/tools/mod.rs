@@ -1,5 +1,9 @@
 pub mod discourse;
+pub mod discourse_recent;
+pub mod github;
 pub mod glean;
+pub mod html;
 pub mod mailing_list;
+pub mod manifest;
 pub mod todoist;
 pub mod web;
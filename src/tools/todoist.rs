@@ -1,12 +1,19 @@
 use anyhow::Context;
+use futures::stream::{self, StreamExt};
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use reqwest::Client;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::time::Duration;
 
+use crate::tools::manifest::{LinkClassifier, ManifestLink};
+
+/// How many comment requests run at once when rendering tasks.
+const COMMENT_CONCURRENCY: usize = 4;
+
 #[derive(Debug, thiserror::Error)]
 pub enum TodoistToolError {
     #[error("Todoist API error (status {status}): {body}")]
@@ -36,6 +43,7 @@ pub struct TodoistTasksTool {
     project_id: String,
     client: Client,
     base_url: String,
+    classifier: LinkClassifier,
 }
 
 #[derive(Deserialize, Debug)]
@@ -47,6 +55,9 @@ pub struct TodoistTasksArgs {
 #[derive(Serialize, Debug)]
 pub struct TodoistTasksOutput {
     pub markdown: String,
+    /// Every distinct URL in the tasks, classified by the tool that should read it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<ManifestLink>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -62,16 +73,114 @@ struct Task {
     order: i32,
     #[serde(rename = "checked")]
     is_completed: bool,
+    #[serde(default)]
+    labels: Vec<String>,
+    /// 1 (normal) to 4 (urgent); shown in the Todoist apps as p4 to p1.
+    #[serde(default = "default_priority")]
+    priority: u8,
+    #[serde(default)]
+    due: Option<Due>,
+    /// Number of comments, when the API reports it. Tasks reporting zero are not queried.
+    #[serde(default)]
+    note_count: Option<u32>,
+}
+
+fn default_priority() -> u8 {
+    1
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Due {
+    date: String,
+    #[serde(default)]
+    string: Option<String>,
+    #[serde(default)]
+    is_recurring: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Comment {
+    content: String,
+    #[serde(default)]
+    posted_at: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 struct Section {
     id: String,
-    #[serde(rename = "section_order")]
+    #[serde(rename = "section_order", default)]
     order: i32,
     name: String,
 }
 
+/// What to do with tasks that were covered by a draft.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskAction {
+    /// Complete the task.
+    Close,
+    /// Move the task into the `Archived/<YYYY-MM>` section of the project.
+    Move,
+}
+
+/// A change made (or, in a dry run, planned) to a covered task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskChange {
+    pub task_id: String,
+    pub content: String,
+    pub action: TaskAction,
+    /// Target section name for [`TaskAction::Move`].
+    pub section: Option<String>,
+}
+
+impl fmt::Display for TaskChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.action, &self.section) {
+            (TaskAction::Move, Some(section)) => {
+                write!(f, "move to {}: {}", section, self.content)
+            }
+            _ => write!(f, "close: {}", self.content),
+        }
+    }
+}
+
+/// Outcome of [`TodoistTasksTool::tidy_covered`].
+#[derive(Debug, Clone, Default)]
+pub struct TidyReport {
+    pub changes: Vec<TaskChange>,
+    /// Open tasks whose links do not appear in the draft.
+    pub uncovered: Vec<String>,
+    pub dry_run: bool,
+}
+
+impl fmt::Display for TidyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.dry_run { "would" } else { "will" };
+        if self.dry_run {
+            writeln!(f, "Todoist dry run, no changes made:")?;
+        } else {
+            writeln!(f, "Todoist tasks updated:")?;
+        }
+        for change in &self.changes {
+            writeln!(f, "  {}", change)?;
+        }
+        if self.changes.is_empty() {
+            writeln!(f, "  (no covered tasks)")?;
+        }
+        if !self.uncovered.is_empty() {
+            writeln!(
+                f,
+                "  {} task(s) not found in the draft {} stay open:",
+                self.uncovered.len(),
+                verb
+            )?;
+            for content in &self.uncovered {
+                writeln!(f, "    {}", content)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Deserialize)]
 struct ApiListResponse<T> {
     results: Vec<T>,
@@ -89,7 +198,7 @@ impl Tool for TodoistTasksTool {
         ToolDefinition {
             name: Self::NAME.to_string(),
             description:
-                "Fetch Todoist tasks for the configured project and return them as Markdown."
+                "Fetch Todoist tasks for the configured project and return them as Markdown, plus a `links` manifest of every URL in the tasks with its kind and the tool to read it with."
                     .to_string(),
             parameters: serde_json::json!({
                 "type": "object",
@@ -121,13 +230,17 @@ impl Tool for TodoistTasksTool {
                     );
                     let tasks = self.fetch_tasks(Some(&s.id)).await?;
                     log::info!("fetched {} tasks", tasks.len());
-                    let markdown = self.render_tasks(&tasks, std::slice::from_ref(s), true);
-                    Ok(TodoistTasksOutput { markdown })
+                    let comments = self.fetch_comments(&tasks).await;
+                    let markdown =
+                        self.render_tasks(&tasks, &comments, std::slice::from_ref(s), true);
+                    let links = self.build_manifest(&tasks);
+                    Ok(TodoistTasksOutput { markdown, links })
                 }
                 None => {
                     log::warn!("section '{}' not found", name);
                     Ok(TodoistTasksOutput {
                         markdown: String::new(),
+                        links: Vec::new(),
                     })
                 }
             }
@@ -136,8 +249,10 @@ impl Tool for TodoistTasksTool {
             let tasks = self.fetch_tasks(None).await?;
             let sections = self.fetch_sections().await?;
             log::info!("fetched {} tasks", tasks.len());
-            let markdown = self.render_tasks(&tasks, &sections, false);
-            Ok(TodoistTasksOutput { markdown })
+            let comments = self.fetch_comments(&tasks).await;
+            let markdown = self.render_tasks(&tasks, &comments, &sections, false);
+            let links = self.build_manifest(&tasks);
+            Ok(TodoistTasksOutput { markdown, links })
         }
     }
 }
@@ -161,9 +276,71 @@ impl TodoistTasksTool {
             project_id,
             client,
             base_url,
+            classifier: LinkClassifier::default(),
         })
     }
 
+    /// Classify task links with the configured Discourse hosts and web allowlist.
+    pub fn with_classifier(mut self, classifier: LinkClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// The link manifest for the tasks in `section` (or the whole project), without
+    /// rendering them or fetching comments.
+    pub async fn manifest(
+        &self,
+        section: Option<&str>,
+    ) -> Result<Vec<ManifestLink>, TodoistToolError> {
+        let tasks = self.tasks_in_section(section).await?.unwrap_or_default();
+        Ok(self.build_manifest(&tasks))
+    }
+
+    /// Tasks in the named section, or the whole project when `section` is empty.
+    /// `None` when the section does not exist.
+    async fn tasks_in_section(
+        &self,
+        section: Option<&str>,
+    ) -> Result<Option<Vec<Task>>, TodoistToolError> {
+        let section_id = match section.filter(|s| !s.trim().is_empty()) {
+            Some(name) => {
+                let sections = self.fetch_sections().await?;
+                match sections.iter().find(|s| s.name.eq_ignore_ascii_case(name)) {
+                    Some(s) => Some(s.id.clone()),
+                    None => {
+                        log::warn!("section '{}' not found", name);
+                        return Ok(None);
+                    }
+                }
+            }
+            None => None,
+        };
+        Ok(Some(self.fetch_tasks(section_id.as_deref()).await?))
+    }
+
+    /// Distinct URLs across task content and descriptions, in task order.
+    fn build_manifest(&self, tasks: &[Task]) -> Vec<ManifestLink> {
+        let mut ordered: Vec<&Task> = tasks.iter().collect();
+        ordered.sort_by_key(|t| (t.parent_id.is_some(), t.order));
+
+        let mut links: Vec<ManifestLink> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for task in ordered {
+            let mut urls = extract_urls(&task.content);
+            urls.extend(extract_urls(&task.description));
+            for url in urls {
+                let i = *index.entry(normalize_url(&url)).or_insert_with(|| {
+                    links.push(self.classifier.classify(&url));
+                    links.len() - 1
+                });
+                if !links[i].tasks.contains(&task.content) {
+                    links[i].tasks.push(task.content.clone());
+                }
+            }
+        }
+        links
+    }
+
     async fn fetch_tasks(&self, section_id: Option<&str>) -> Result<Vec<Task>, TodoistToolError> {
         let mut tasks = Vec::new();
         let mut cursor: Option<String> = None;
@@ -206,6 +383,74 @@ impl TodoistTasksTool {
         Ok(tasks)
     }
 
+    /// Comments for every task that may have some, keyed by task id, with at most
+    /// `COMMENT_CONCURRENCY` requests in flight. Comments only add context, so a failed
+    /// request is logged and the task rendered without them.
+    async fn fetch_comments(&self, tasks: &[Task]) -> HashMap<String, Vec<Comment>> {
+        let ids: Vec<String> = tasks
+            .iter()
+            .filter(|t| t.note_count != Some(0))
+            .map(|t| t.id.clone())
+            .collect();
+        let results: Vec<_> = stream::iter(ids)
+            .map(|id| async move {
+                let result = self.fetch_task_comments(&id).await;
+                (id, result)
+            })
+            .buffered(COMMENT_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut comments = HashMap::new();
+        for (id, result) in results {
+            match result {
+                Ok(list) if !list.is_empty() => {
+                    comments.insert(id, list);
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!("skipping comments for task {}: {}", id, err),
+            }
+        }
+        comments
+    }
+
+    async fn fetch_task_comments(&self, task_id: &str) -> Result<Vec<Comment>, TodoistToolError> {
+        let mut comments = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut url = format!("{}/api/v1/comments?task_id={}", self.base_url, task_id);
+            if let Some(c) = &cursor {
+                url.push_str("&cursor=");
+                url.push_str(c);
+            }
+
+            let response = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .context("Todoist comments request failed")?;
+
+            if !response.status().is_success() {
+                let status = response.status().as_u16();
+                let body = response.text().await.unwrap_or_default();
+                return Err(TodoistToolError::ApiStatus { status, body });
+            }
+
+            let body = response.text().await.context("Todoist comments body")?;
+            let response_data: ApiListResponse<Comment> =
+                serde_json::from_str(&body).context("Todoist comments JSON")?;
+            comments.extend(response_data.results);
+            cursor = response_data.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        comments.sort_by(|a, b| a.posted_at.cmp(&b.posted_at));
+        Ok(comments)
+    }
+
     async fn fetch_sections(&self) -> Result<Vec<Section>, TodoistToolError> {
         let mut sections = Vec::new();
         let mut cursor: Option<String> = None;
@@ -245,9 +490,140 @@ impl TodoistTasksTool {
         Ok(sections)
     }
 
+    /// Close or archive the open tasks whose links appear in `draft`. A top-level task counts
+    /// as covered when any link in it or its subtasks is in the draft; subtasks follow their
+    /// parent. Tasks without links are left alone. With `dry_run` nothing is changed.
+    pub async fn tidy_covered(
+        &self,
+        draft: &str,
+        section: Option<&str>,
+        action: TaskAction,
+        month: &str,
+        dry_run: bool,
+    ) -> Result<TidyReport, TodoistToolError> {
+        let Some(tasks) = self.tasks_in_section(section).await? else {
+            return Ok(TidyReport {
+                dry_run,
+                ..TidyReport::default()
+            });
+        };
+
+        let drafted: HashSet<String> = extract_urls(draft)
+            .iter()
+            .map(|u| normalize_url(u))
+            .collect();
+        let mut children: HashMap<&str, Vec<&Task>> = HashMap::new();
+        for task in &tasks {
+            if let Some(parent) = &task.parent_id {
+                children.entry(parent).or_default().push(task);
+            }
+        }
+
+        let mut roots: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| !t.is_completed)
+            .filter(|t| {
+                t.parent_id
+                    .as_deref()
+                    .is_none_or(|p| !tasks.iter().any(|o| o.id == p))
+            })
+            .collect();
+        roots.sort_by_key(|t| t.order);
+
+        let archive_section = format!("Archived/{}", month);
+        let mut report = TidyReport {
+            dry_run,
+            ..TidyReport::default()
+        };
+        for task in roots {
+            let urls = task_tree_urls(task, &children);
+            if urls.is_empty() {
+                continue;
+            }
+            if !urls.iter().any(|u| drafted.contains(&normalize_url(u))) {
+                report.uncovered.push(task.content.clone());
+                continue;
+            }
+            report.changes.push(TaskChange {
+                task_id: task.id.clone(),
+                content: task.content.clone(),
+                action,
+                section: (action == TaskAction::Move).then(|| archive_section.clone()),
+            });
+        }
+
+        if dry_run || report.changes.is_empty() {
+            return Ok(report);
+        }
+
+        let target = match action {
+            TaskAction::Close => None,
+            TaskAction::Move => Some(
+                match self
+                    .fetch_sections()
+                    .await?
+                    .iter()
+                    .find(|s| s.name == archive_section)
+                {
+                    Some(s) => s.id.clone(),
+                    None => self.create_section(&archive_section).await?.id,
+                },
+            ),
+        };
+        for change in &report.changes {
+            log::info!("todoist: {}", change);
+            match &target {
+                None => {
+                    self.post(
+                        &format!("{}/api/v1/tasks/{}/close", self.base_url, change.task_id),
+                        None,
+                    )
+                    .await?;
+                }
+                Some(section_id) => {
+                    self.post(
+                        &format!("{}/api/v1/tasks/{}/move", self.base_url, change.task_id),
+                        Some(serde_json::json!({ "section_id": section_id })),
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn create_section(&self, name: &str) -> Result<Section, TodoistToolError> {
+        let body = self
+            .post(
+                &format!("{}/api/v1/sections", self.base_url),
+                Some(serde_json::json!({ "name": name, "project_id": self.project_id })),
+            )
+            .await?;
+        Ok(serde_json::from_str(&body).context("Todoist section JSON")?)
+    }
+
+    async fn post(
+        &self,
+        url: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<String, TodoistToolError> {
+        let mut request = self.client.post(url);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.context("Todoist request failed")?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            return Err(TodoistToolError::ApiStatus { status, body });
+        }
+        Ok(response.text().await.context("Todoist response body")?)
+    }
+
     fn render_tasks(
         &self,
         tasks: &[Task],
+        comments: &HashMap<String, Vec<Comment>>,
         sections: &[Section],
         hide_section_headers: bool,
     ) -> String {
@@ -283,7 +659,7 @@ impl TodoistTasksTool {
                     output.push_str(&format!("## {}\n\n", section.name));
                 }
                 for task in tasks {
-                    format_task_recursive(task, &tasks_by_parent, 0, &mut output);
+                    format_task_recursive(task, &tasks_by_parent, comments, 0, &mut output);
                 }
                 if !hide_section_headers {
                     output.push('\n');
@@ -297,7 +673,7 @@ impl TodoistTasksTool {
                     output.push_str("## (No Section)\n\n");
                 }
                 for task in tasks {
-                    format_task_recursive(task, &tasks_by_parent, 0, &mut output);
+                    format_task_recursive(task, &tasks_by_parent, comments, 0, &mut output);
                 }
             }
         }
@@ -306,9 +682,65 @@ impl TodoistTasksTool {
     }
 }
 
+/// Links in a task's content and description and those of all its subtasks.
+fn task_tree_urls(task: &Task, children: &HashMap<&str, Vec<&Task>>) -> Vec<String> {
+    let mut urls = extract_urls(&task.content);
+    urls.extend(extract_urls(&task.description));
+    for child in children.get(task.id.as_str()).into_iter().flatten() {
+        urls.extend(task_tree_urls(child, children));
+    }
+    urls
+}
+
+/// Every `http(s)://` URL in `text`, whether bare or inside a markdown link.
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("http") {
+        let candidate = &rest[start..];
+        if !(candidate.starts_with("http://") || candidate.starts_with("https://")) {
+            rest = &candidate[4..];
+            continue;
+        }
+        // A `)` only ends the URL when it closes nothing inside it, so Wikipedia-style
+        // `a_(b)` paths survive inside `[text](url)`.
+        let mut depth = 0usize;
+        let end = candidate
+            .find(|c: char| match c {
+                '(' => {
+                    depth += 1;
+                    false
+                }
+                ')' if depth > 0 => {
+                    depth -= 1;
+                    false
+                }
+                c => c.is_whitespace() || matches!(c, ')' | ']' | '>' | '<' | '"' | '`'),
+            })
+            .unwrap_or(candidate.len());
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', '\'']);
+        if url.len() > "https://".len() {
+            urls.push(url.to_string());
+        }
+        rest = &candidate[end..];
+    }
+    urls
+}
+
+/// Compare URLs ignoring scheme, case, fragments and trailing slashes.
+fn normalize_url(url: &str) -> String {
+    let url = url.split('#').next().unwrap_or(url);
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    url.trim_end_matches('/').to_ascii_lowercase()
+}
+
 fn format_task_recursive(
     task: &Task,
     tasks_by_parent: &HashMap<Option<String>, Vec<Task>>,
+    comments: &HashMap<String, Vec<Comment>>,
     indent_level: usize,
     output: &mut String,
 ) {
@@ -316,20 +748,49 @@ fn format_task_recursive(
     let checkbox = if task.is_completed { "[x]" } else { "[ ]" };
     output.push_str(&format!("{}- {} {}\n", indent, checkbox, task.content));
 
-    if !task.description.is_empty() {
-        let desc_indent = "  ".repeat(indent_level + 1);
-        let mut lines = task.description.lines();
-        if let Some(first) = lines.next() {
-            output.push_str(&format!("{}- **Description**: {}\n", desc_indent, first));
-            for line in lines {
-                output.push_str(&format!("{}  {}\n", desc_indent, line));
+    let detail_indent = "  ".repeat(indent_level + 1);
+    if !task.labels.is_empty() {
+        push_detail(output, &detail_indent, "Labels", &task.labels.join(", "));
+    }
+    if task.priority > 1 {
+        // The API counts up to 4 for urgent; the apps call that p1.
+        let shown = 5 - task.priority.min(4);
+        push_detail(output, &detail_indent, "Priority", &format!("p{}", shown));
+    }
+    if let Some(due) = &task.due {
+        let value = match &due.string {
+            Some(text) if due.is_recurring && !text.is_empty() => {
+                format!("{} ({})", due.date, text)
             }
-        }
+            _ => due.date.clone(),
+        };
+        push_detail(output, &detail_indent, "Due", &value);
+    }
+    if !task.description.is_empty() {
+        push_detail(output, &detail_indent, "Description", &task.description);
+    }
+    for comment in comments.get(&task.id).into_iter().flatten() {
+        let label = match comment.posted_at.as_deref().and_then(|p| p.get(..10)) {
+            Some(date) => format!("Comment ({})", date),
+            None => "Comment".to_string(),
+        };
+        push_detail(output, &detail_indent, &label, &comment.content);
     }
 
     if let Some(children) = tasks_by_parent.get(&Some(task.id.clone())) {
         for child in children {
-            format_task_recursive(child, tasks_by_parent, indent_level + 1, output);
+            format_task_recursive(child, tasks_by_parent, comments, indent_level + 1, output);
+        }
+    }
+}
+
+/// Append a `- **label**: value` bullet, indenting continuation lines of `value` under it.
+fn push_detail(output: &mut String, indent: &str, label: &str, value: &str) {
+    let mut lines = value.lines();
+    if let Some(first) = lines.next() {
+        output.push_str(&format!("{}- **{}**: {}\n", indent, label, first));
+        for line in lines {
+            output.push_str(&format!("{}  {}\n", indent, line));
         }
     }
 }
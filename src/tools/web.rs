@@ -2,48 +2,32 @@ use anyhow::Context;
 use readability::extractor;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Cursor;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use url::Url;
 
+use crate::cache::{fetch_text, HttpCache};
+use crate::config::de::{deserialize_option_u64, deserialize_option_usize};
+use crate::tools::html::{extract_links, html_to_markdown, Link};
+
+/// Most links a page result lists.
+const MAX_LINKS: usize = 100;
+
 #[derive(Debug, thiserror::Error)]
 pub enum WebReadabilityToolError {
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
     #[error("Disallowed host: {0}")]
     DisallowedHost(String),
+    #[error("Link check failed for {url}: {reason}")]
+    BrokenLink { url: String, reason: String },
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
-fn deserialize_option_usize<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s: Option<String> = Option::deserialize(deserializer)?;
-    if let Some(s) = s {
-        s.parse::<usize>()
-            .map(Some)
-            .map_err(serde::de::Error::custom)
-    } else {
-        Ok(None)
-    }
-}
-
-fn deserialize_option_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s: Option<String> = Option::deserialize(deserializer)?;
-    if let Some(s) = s {
-        s.parse::<u64>().map(Some).map_err(serde::de::Error::custom)
-    } else {
-        Ok(None)
-    }
-}
-
 #[derive(Deserialize, Debug, Clone, Default)]
 pub struct WebConfig {
     #[serde(rename = "web_allowlist")]
@@ -74,21 +58,27 @@ pub struct WebConfig {
 pub struct WebReadabilityTool {
     allowlist: Vec<String>,
     max_chars: usize,
-    min_interval: Duration,
-    last_request: Arc<Mutex<Option<Instant>>>,
+    limiter: HostLimiter,
     client: reqwest::Client,
+    cache: Option<HttpCache>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct WebReadabilityArgs {
-    /// URL to fetch and extract text from.
+    /// URL to fetch and extract content from.
     pub url: String,
 }
 
 #[derive(Serialize, Debug)]
 pub struct WebReadabilityOutput {
     pub title: String,
+    /// The readable content rendered as markdown.
     pub text: String,
+    /// Links in the readable content, deduplicated, including any cut from `text`. Same-page
+    /// links are left out, and the list shares `max_chars` with `text`.
+    pub links: Vec<Link>,
+    /// Links left out of `links` to stay within the limits.
+    pub omitted_links: usize,
     pub source_url: String,
     pub truncated: bool,
 }
@@ -103,7 +93,7 @@ impl Tool for WebReadabilityTool {
     async fn definition(&self, _prompt: String) -> ToolDefinition {
         ToolDefinition {
             name: Self::NAME.to_string(),
-            description: "Fetch a web page, extract the main content using Readability, and return plain text."
+            description: "Fetch a web page, extract the main content using Readability, and return it as markdown with links, code blocks, lists and headings preserved, plus a deduplicated list of the links on the page with their anchor text."
                 .to_string(),
             parameters: serde_json::json!({
                 "type": "object",
@@ -129,25 +119,49 @@ impl Tool for WebReadabilityTool {
             return Err(WebReadabilityToolError::DisallowedHost(host.to_string()));
         }
 
-        self.wait_for_rate_limit().await;
-
         let url_string = url.to_string();
         let source_url = url_string.clone();
-        let response = self
-            .client
-            .get(&url_string)
-            .send()
-            .await
-            .context("Web request failed")?
-            .error_for_status()
-            .context("Web request returned error status")?;
-        let body = response.text().await.context("Web response body")?;
-        let mut cursor = Cursor::new(body);
+        // Cache hits make no request, so they need not wait their turn.
+        if !self
+            .cache
+            .as_ref()
+            .is_some_and(|cache| cache.is_fresh(&url_string))
+        {
+            self.limiter.wait(host).await;
+        }
+        let body = fetch_text(
+            self.cache.as_ref(),
+            self.client.get(&url_string),
+            &url_string,
+        )
+        .await
+        .context("Web request failed")?;
+        let mut cursor = Cursor::new(body.into_bytes());
         let product = extractor::extract(&mut cursor, &url).context("Readability extract")?;
 
-        let mut text = product.text;
-        let truncated = if text.chars().count() > self.max_chars {
-            text = text.chars().take(self.max_chars).collect::<String>();
+        let mut text = html_to_markdown(&product.content, Some(&url));
+        let mut links = extract_links(&product.content, Some(&url));
+        links.retain(|link| !is_same_page(&link.url, &url));
+
+        // Links may use up to a quarter of `max_chars`; the text gets the rest.
+        let link_budget = self.max_chars / 4;
+        let found_links = links.len();
+        let mut link_chars = 0;
+        let mut kept = Vec::new();
+        for link in links.into_iter().take(MAX_LINKS) {
+            let chars = link.text.chars().count() + link.url.chars().count();
+            if link_chars + chars > link_budget {
+                break;
+            }
+            link_chars += chars;
+            kept.push(link);
+        }
+        let links = kept;
+        let omitted_links = found_links - links.len();
+        let text_budget = self.max_chars - link_chars;
+
+        let truncated = if text.chars().count() > text_budget {
+            text = text.chars().take(text_budget).collect::<String>();
             true
         } else {
             false
@@ -156,6 +170,8 @@ impl Tool for WebReadabilityTool {
         Ok(WebReadabilityOutput {
             title: product.title,
             text,
+            links,
+            omitted_links,
             source_url,
             truncated,
         })
@@ -189,13 +205,61 @@ impl WebReadabilityTool {
         Ok(Self {
             allowlist,
             max_chars,
-            min_interval: Duration::from_millis(min_interval_ms),
-            last_request: Arc::new(Mutex::new(None)),
+            limiter: HostLimiter::new(Duration::from_millis(min_interval_ms)),
             client,
+            cache: None,
         })
     }
 
-    fn is_host_allowed(&self, host: &str) -> bool {
+    /// Route page fetches through an on-disk response cache.
+    pub fn with_cache(mut self, cache: Option<HttpCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Check that `url` resolves, honouring the allowlist and rate limit but bypassing the
+    /// response cache. Falls back to GET for servers that reject HEAD.
+    pub async fn check_link(&self, url: &str) -> Result<(), WebReadabilityToolError> {
+        let parsed =
+            Url::parse(url).map_err(|_| WebReadabilityToolError::InvalidUrl(url.to_string()))?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| WebReadabilityToolError::InvalidUrl(url.to_string()))?;
+        if !self.is_host_allowed(host) {
+            return Err(WebReadabilityToolError::DisallowedHost(host.to_string()));
+        }
+
+        let broken = |reason: String| WebReadabilityToolError::BrokenLink {
+            url: url.to_string(),
+            reason,
+        };
+
+        self.limiter.wait(host).await;
+        let mut response = self
+            .client
+            .head(parsed.clone())
+            .send()
+            .await
+            .map_err(|err| broken(err.to_string()))?;
+        if matches!(response.status().as_u16(), 403 | 405 | 501) {
+            self.limiter.wait(host).await;
+            response = self
+                .client
+                .get(parsed)
+                .send()
+                .await
+                .map_err(|err| broken(err.to_string()))?;
+        }
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(broken(format!("HTTP {}", status.as_u16())))
+        }
+    }
+
+    pub(crate) fn is_host_allowed(&self, host: &str) -> bool {
         if self.allowlist.is_empty() {
             return true;
         }
@@ -210,19 +274,55 @@ impl WebReadabilityTool {
         })
     }
 
-    async fn wait_for_rate_limit(&self) {
+    /// The per-host limiter for this tool's fetches. Clones share its state, so other
+    /// fetchers can be spaced against the same hosts.
+    pub fn limiter(&self) -> &HostLimiter {
+        &self.limiter
+    }
+}
+
+/// Whether `link` points back at `page`, possibly at an anchor on it.
+fn is_same_page(link: &str, page: &Url) -> bool {
+    let Ok(mut link) = Url::parse(link) else {
+        return false;
+    };
+    let mut page = page.clone();
+    link.set_fragment(None);
+    page.set_fragment(None);
+    link == page
+}
+
+/// Spaces out requests to each host by a minimum interval. Clones share their state.
+#[derive(Debug, Clone)]
+pub struct HostLimiter {
+    min_interval: Duration,
+    last_request: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl HostLimiter {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_request: Arc::default(),
+        }
+    }
+
+    /// Wait until `min_interval` has passed since the previous request to `host`.
+    pub async fn wait(&self, host: &str) {
         if self.min_interval == Duration::from_millis(0) {
             return;
         }
         let sleep_for = {
             let mut guard = self.last_request.lock().unwrap();
             let now = Instant::now();
-            let sleep_for = match *guard {
-                Some(last) => self.min_interval.saturating_sub(now.duration_since(last)),
-                None => Duration::from_millis(0),
+            // Reserve the next free slot, which may already lie in the future when several
+            // requests to the host are waiting.
+            let slot = match guard.get(host) {
+                Some(last) => (*last + self.min_interval).max(now),
+                None => now,
             };
-            *guard = Some(now + sleep_for);
-            sleep_for
+            guard.insert(host.to_string(), slot);
+            slot - now
         };
         if sleep_for > Duration::from_millis(0) {
             tokio::time::sleep(sleep_for).await;
@@ -1,16 +1,29 @@
+use newsagent::agent::audit::AuditConfig;
+use newsagent::agent::links::LinkCheckConfig;
+use newsagent::agent::prefetch::PrefetchConfig;
+use newsagent::agent::prompt::PromptConfig;
+use newsagent::agent::provider::{LlmConfig, LlmProvider};
+use newsagent::agent::validate::ValidateConfig;
 use newsagent::agent::Agent;
+use newsagent::cache::CacheConfig;
 use newsagent::config::AppConfig;
+use newsagent::output::OutputConfig;
 use newsagent::tools::discourse::DiscourseConfig;
+use newsagent::tools::github::GithubConfig;
 use newsagent::tools::glean::GleanConfig;
 use newsagent::tools::mailing_list::MailingListConfig;
 use newsagent::tools::todoist::TodoistConfig;
 use newsagent::tools::web::WebConfig;
+use tempfile::tempdir;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
 
-#[test]
-fn agent_new_fails_when_glean_dir_missing() {
-    let config = AppConfig {
-        gemini_api_key: "test-key".to_string(),
+fn test_config(glean_dir: &str, llm: LlmConfig) -> AppConfig {
+    AppConfig {
+        gemini_api_key: Some("test-key".to_string()),
         gemini_model: "test-model".to_string(),
+        profile: None,
+        llm,
         todoist: TodoistConfig {
             api_token: "todo-token".to_string(),
             project_id: "project-id".to_string(),
@@ -18,13 +31,26 @@ fn agent_new_fails_when_glean_dir_missing() {
             base_url: "https://api.todoist.com".to_string(),
         },
         glean: GleanConfig {
-            dir: "/does/not/exist".to_string(),
+            dir: glean_dir.to_string(),
             filter: None,
         },
         web: WebConfig::default(),
+        github: GithubConfig::default(),
         discourse: DiscourseConfig::default(),
         mailing_list: MailingListConfig::default(),
-    };
+        output: OutputConfig::default(),
+        cache: CacheConfig::default(),
+        audit: AuditConfig::default(),
+        validate: ValidateConfig::default(),
+        links: LinkCheckConfig::default(),
+        prompt: PromptConfig::default(),
+        prefetch: PrefetchConfig::default(),
+    }
+}
+
+#[test]
+fn agent_new_fails_when_glean_dir_missing() {
+    let config = test_config("/does/not/exist", LlmConfig::default());
 
     match Agent::new(config) {
         Ok(_) => panic!("Expected Agent::new to fail"),
@@ -34,3 +60,62 @@ fn agent_new_fails_when_glean_dir_missing() {
         }
     }
 }
+
+#[test]
+fn agent_new_fails_without_gemini_api_key() {
+    let dir = tempdir().expect("Failed to create tempdir");
+    let mut config = test_config(&dir.path().to_string_lossy(), LlmConfig::default());
+    config.gemini_api_key = None;
+
+    match Agent::new(config) {
+        Ok(_) => panic!("Expected Agent::new to fail"),
+        Err(err) => {
+            assert!(err.to_string().contains("NEWSAGENT_GEMINI_API_KEY"));
+        }
+    }
+}
+
+#[tokio::test]
+async fn agent_prompts_openai_compatible_endpoint() {
+    let server = MockServer::start().await;
+    let dir = tempdir().expect("Failed to create tempdir");
+
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(
+            serde_json::json!({ "model": "local-model" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "local-model",
+            "choices": [
+                {
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "### 🪨 Draft" },
+                    "finish_reason": "stop"
+                }
+            ],
+            "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config = test_config(
+        &dir.path().to_string_lossy(),
+        LlmConfig {
+            provider: LlmProvider::OpenAi,
+            api_key: None,
+            model: Some("local-model".to_string()),
+            base_url: Some(format!("{}/v1", server.uri())),
+            max_tokens: None,
+        },
+    );
+
+    let agent = Agent::new(config).expect("Failed to build agent");
+    let response = agent.prompt().await.expect("Prompt failed");
+
+    assert_eq!(response, "### 🪨 Draft");
+}
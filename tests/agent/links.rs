@@ -0,0 +1,102 @@
+use newsagent::agent::links::{append_link_notes, check_links, BrokenLink};
+use newsagent::tools::web::{WebConfig, WebReadabilityTool};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn broken(entry: &str, url: &str, reason: &str) -> BrokenLink {
+    BrokenLink {
+        entry: entry.to_string(),
+        url: url.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn reports_unreachable_links_under_their_entry() {
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/ok"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/gone"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/no-head"))
+        .respond_with(ResponseTemplate::new(405))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/no-head"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let web = WebReadabilityTool::new(WebConfig {
+        allowlist: Some("127.0.0.1".to_string()),
+        ..WebConfig::default()
+    })
+    .expect("Failed to create web tool");
+
+    let uri = server.uri();
+    let draft = format!(
+        "### 🪨 Pebble [1.27.0]({uri}/ok)\n\nSee [notes]({uri}/gone), [docs]({uri}/no-head), [again]({uri}/ok) and [elsewhere](https://example.com/x).\n\n## ✏️ Editor Review Notes\n\n- [ ] [Pebble] — [ignored]({uri}/notes-only)\n"
+    );
+
+    let result = check_links(&web, &draft).await;
+
+    assert_eq!(
+        result,
+        vec![broken("Pebble 1.27.0", &format!("{uri}/gone"), "HTTP 404")]
+    );
+}
+
+#[test]
+fn appends_to_existing_links_section() {
+    let draft = "### 🪨 Pebble\n\nText.\n\n---\n\n## ✏️ Editor Review Notes\n\n### 🔗 Links to verify\n- [ ] [Pebble] — docs link inferred\n\n### ❓ Details to confirm\n- [ ] [Pebble] — version\n";
+
+    let output = append_link_notes(
+        draft,
+        &[broken("Pebble", "https://x.test/gone", "HTTP 404")],
+    );
+
+    assert_eq!(
+        output,
+        "### 🪨 Pebble\n\nText.\n\n---\n\n## ✏️ Editor Review Notes\n\n### 🔗 Links to verify\n- [ ] [Pebble] — docs link inferred\n- [ ] [Pebble] — https://x.test/gone could not be reached (HTTP 404)\n\n### ❓ Details to confirm\n- [ ] [Pebble] — version\n"
+    );
+}
+
+#[test]
+fn adds_links_section_to_existing_notes() {
+    let draft = "### 🪨 Pebble\n\n## ✏️ Editor Review Notes\n\n### ❓ Details to confirm\n- [ ] [Pebble] — version\n";
+
+    let output = append_link_notes(draft, &[broken("Pebble", "https://x.test/a", "HTTP 500")]);
+
+    assert_eq!(
+        output,
+        "### 🪨 Pebble\n\n## ✏️ Editor Review Notes\n\n### 🔗 Links to verify\n- [ ] [Pebble] — https://x.test/a could not be reached (HTTP 500)\n\n### ❓ Details to confirm\n- [ ] [Pebble] — version\n"
+    );
+}
+
+#[test]
+fn creates_notes_when_missing() {
+    let output = append_link_notes(
+        "### 🪨 Pebble\n\nText.\n",
+        &[broken("Pebble", "https://x.test/a", "HTTP 404")],
+    );
+
+    assert_eq!(
+        output,
+        "### 🪨 Pebble\n\nText.\n\n---\n\n## ✏️ Editor Review Notes\n\n### 🔗 Links to verify\n- [ ] [Pebble] — https://x.test/a could not be reached (HTTP 404)\n"
+    );
+}
+
+#[test]
+fn leaves_draft_untouched_without_failures() {
+    assert_eq!(append_link_notes("### 🪨 Pebble\n", &[]), "### 🪨 Pebble\n");
+}
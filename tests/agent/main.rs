@@ -1,2 +1,7 @@
 mod agent;
+mod links;
+mod mock;
+mod pipeline;
+mod prefetch;
 mod prompt;
+mod validate;
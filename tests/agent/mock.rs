@@ -0,0 +1,143 @@
+use rig::completion::message::{AssistantContent, ToolResultContent, UserContent};
+use rig::completion::{
+    CompletionError, CompletionModel, CompletionRequest, CompletionResponse, Message, Usage,
+};
+use rig::streaming::StreamingCompletionResponse;
+use rig::OneOrMany;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// One scripted model reply: either a batch of tool calls or the final text.
+#[derive(Debug, Clone)]
+pub enum Turn {
+    ToolCalls(Vec<(&'static str, serde_json::Value)>),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptedResponse;
+
+/// A completion model that replays a fixed sequence of turns and records every request
+/// it receives, so tests can drive `Agent::prompt` without a real LLM.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedModel {
+    turns: Arc<Mutex<VecDeque<Turn>>>,
+    requests: Arc<Mutex<Vec<CompletionRequest>>>,
+}
+
+impl ScriptedModel {
+    pub fn new(turns: impl IntoIterator<Item = Turn>) -> Self {
+        Self {
+            turns: Arc::new(Mutex::new(turns.into_iter().collect())),
+            requests: Arc::default(),
+        }
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Names of the tools advertised to the model on the first request.
+    pub fn advertised_tools(&self) -> Vec<String> {
+        let requests = self.requests.lock().unwrap();
+        let mut names: Vec<String> = requests
+            .first()
+            .map(|r| r.tools.iter().map(|t| t.name.clone()).collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Text of the prompt (the final user message) in the most recent request.
+    pub fn last_prompt(&self) -> String {
+        let requests = self.requests.lock().unwrap();
+        let Some(Message::User { content }) = requests.last().map(|r| r.chat_history.last()) else {
+            return String::new();
+        };
+        content
+            .iter()
+            .filter_map(|c| match c {
+                UserContent::Text(t) => Some(t.text().to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Text of every tool result fed back to the model, keyed by tool call id.
+    pub fn tool_results(&self) -> Vec<(String, String)> {
+        let requests = self.requests.lock().unwrap();
+        let Some(last) = requests.last() else {
+            return Vec::new();
+        };
+        let mut results = Vec::new();
+        for message in last.chat_history.iter() {
+            let Message::User { content } = message else {
+                continue;
+            };
+            for item in content.iter() {
+                if let UserContent::ToolResult(result) = item {
+                    let text = result
+                        .content
+                        .iter()
+                        .filter_map(|c| match c {
+                            ToolResultContent::Text(t) => Some(t.text().to_string()),
+                            _ => None,
+                        })
+                        .collect::<String>();
+                    results.push((result.id.clone(), text));
+                }
+            }
+        }
+        results
+    }
+}
+
+impl CompletionModel for ScriptedModel {
+    type Response = ScriptedResponse;
+    type StreamingResponse = ();
+    type Client = ();
+
+    fn make(_client: &Self::Client, _model: impl Into<String>) -> Self {
+        Self::default()
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
+        let turn_number = {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(request);
+            requests.len()
+        };
+        let turn = self.turns.lock().unwrap().pop_front().ok_or_else(|| {
+            CompletionError::ProviderError(format!("script exhausted at turn {}", turn_number))
+        })?;
+
+        let choice = match turn {
+            Turn::Text(text) => OneOrMany::one(AssistantContent::text(text)),
+            Turn::ToolCalls(calls) => {
+                OneOrMany::many(calls.into_iter().enumerate().map(|(i, (name, args))| {
+                    AssistantContent::tool_call(format!("call-{}-{}", turn_number, i), name, args)
+                }))
+                .map_err(|_| CompletionError::ProviderError("empty tool call turn".to_string()))?
+            }
+        };
+
+        Ok(CompletionResponse {
+            choice,
+            usage: Usage::new(),
+            raw_response: ScriptedResponse,
+        })
+    }
+
+    async fn stream(
+        &self,
+        _request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
+        Err(CompletionError::ProviderError(
+            "streaming is not scripted".to_string(),
+        ))
+    }
+}
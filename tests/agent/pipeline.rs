@@ -0,0 +1,525 @@
+use crate::mock::{ScriptedModel, Turn};
+use chrono::{Duration, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use newsagent::agent::audit::{AuditConfig, AuditRecord};
+use newsagent::agent::links::LinkCheckConfig;
+use newsagent::agent::prefetch::PrefetchConfig;
+use newsagent::agent::prompt::PromptConfig;
+use newsagent::agent::provider::LlmConfig;
+use newsagent::agent::validate::ValidateConfig;
+use newsagent::agent::Agent;
+use newsagent::cache::CacheConfig;
+use newsagent::config::AppConfig;
+use newsagent::output::OutputConfig;
+use newsagent::tools::discourse::{DiscourseConfig, DiscourseInstance};
+use newsagent::tools::github::GithubConfig;
+use newsagent::tools::glean::GleanConfig;
+use newsagent::tools::mailing_list::MailingListConfig;
+use newsagent::tools::todoist::TodoistConfig;
+use newsagent::tools::web::WebConfig;
+use serde_json::json;
+use std::io::Write;
+use tempfile::{tempdir, TempDir};
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn gzip_bytes(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).expect("gzip write failed");
+    encoder.finish().expect("gzip finish failed")
+}
+
+async fn mount_services(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/api/v1/sections"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [],
+            "next_cursor": null
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [
+                {
+                    "id": "1",
+                    "content": "Pebble 1.27.0 released",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false
+                }
+            ],
+            "next_cursor": null
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/release"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            "<html><head><title>Pebble 1.27.0</title></head><body><article><p>Adds a syslog log target.</p></article></body></html>",
+        ))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/t/42.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "title": "Juju 3.6.5 announcement",
+            "post_stream": {
+                "posts": [
+                    {
+                        "post_number": 1,
+                        "username": "alice",
+                        "created_at": "2025-06-01T12:00:00Z",
+                        "cooked": "<p>Juju 3.6.5 is out</p>"
+                    }
+                ]
+            }
+        })))
+        .mount(server)
+        .await;
+
+    let date = (Utc::now() - Duration::days(2))
+        .format("%a, %d %b %Y %H:%M:%S +0000")
+        .to_string();
+    let mbox = format!(
+        "From alice@example.com {date}\nFrom: Alice <alice@example.com>\nSubject: Questing archive opening\nDate: {date}\nMessage-ID: <open@example.com>\nContent-Type: text/plain; charset=\"UTF-8\"\n\nThe archive is open.\n\n"
+    );
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/archives/ubuntu-devel/{}.txt.gz",
+            Utc::now().format("%Y-%B")
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(mbox.as_bytes())))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/archives/.*\.txt\.gz$"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(server)
+        .await;
+}
+
+fn pipeline_config(server: &MockServer, glean_dir: &TempDir) -> AppConfig {
+    AppConfig {
+        gemini_api_key: None,
+        gemini_model: "unused".to_string(),
+        profile: None,
+        llm: LlmConfig::default(),
+        todoist: TodoistConfig {
+            api_token: "todo-token".to_string(),
+            project_id: "proj-1".to_string(),
+            project_section: None,
+            base_url: server.uri(),
+        },
+        glean: GleanConfig {
+            dir: glean_dir.path().to_string_lossy().to_string(),
+            filter: None,
+        },
+        web: WebConfig {
+            allowlist: Some("127.0.0.1".to_string()),
+            ..WebConfig::default()
+        },
+        github: GithubConfig::default(),
+        discourse: DiscourseConfig {
+            instances: vec![DiscourseInstance {
+                base_url: server.uri().replace("http://", ""),
+                api_key: None,
+                api_username: None,
+            }],
+            ..DiscourseConfig::default()
+        },
+        mailing_list: MailingListConfig {
+            lists: vec!["ubuntu-devel".to_string()],
+            base_url: Some(format!("{}/archives", server.uri())),
+            ..MailingListConfig::default()
+        },
+        output: OutputConfig::default(),
+        cache: CacheConfig::default(),
+        audit: AuditConfig::default(),
+        validate: ValidateConfig::default(),
+        links: LinkCheckConfig::default(),
+        prompt: PromptConfig::default(),
+        prefetch: PrefetchConfig::default(),
+    }
+}
+
+#[tokio::test]
+async fn runs_full_tool_loop_against_scripted_model() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let model = ScriptedModel::new([
+        Turn::ToolCalls(vec![("todoist_tasks", json!({}))]),
+        Turn::ToolCalls(vec![
+            (
+                "browse_web",
+                json!({ "url": format!("{}/release", server.uri()) }),
+            ),
+            (
+                "discourse_fetch",
+                json!({ "url": format!("{}/t/juju-365/42", server.uri()) }),
+            ),
+            ("mailing_list_threads", json!({})),
+        ]),
+        Turn::Text("### 🪨 Pebble 1.27.0\n\nAll done.".to_string()),
+    ]);
+
+    let agent = Agent::with_model(pipeline_config(&server, &glean_dir), model.clone())
+        .expect("Failed to build agent");
+    let response = agent.prompt().await.expect("Prompt failed");
+
+    assert_eq!(response, "### 🪨 Pebble 1.27.0\n\nAll done.");
+    assert_eq!(model.request_count(), 3);
+    assert_eq!(
+        model.advertised_tools(),
+        vec![
+            "browse_web",
+            "discourse_fetch",
+            "github_release",
+            "local_markdown_context",
+            "mailing_list_threads",
+            "todoist_tasks",
+        ]
+    );
+
+    let results = model.tool_results();
+    assert_eq!(results.len(), 4);
+    let all = results
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(all.contains("Pebble 1.27.0 released"));
+    assert!(all.contains("Adds a syslog log target."));
+    assert!(all.contains("Juju 3.6.5 is out"));
+    assert!(all.contains("Questing archive opening"));
+}
+
+#[tokio::test]
+async fn optional_tools_are_not_registered_when_unconfigured() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.discourse = DiscourseConfig::default();
+    config.mailing_list = MailingListConfig::default();
+
+    let model = ScriptedModel::new([
+        Turn::ToolCalls(vec![(
+            "discourse_fetch",
+            json!({ "url": format!("{}/t/juju-365/42", server.uri()) }),
+        )]),
+        Turn::Text("Draft".to_string()),
+    ]);
+
+    let agent = Agent::with_model(config, model.clone()).expect("Failed to build agent");
+    let response = agent.prompt().await.expect("Prompt failed");
+
+    assert_eq!(response, "Draft");
+    assert_eq!(
+        model.advertised_tools(),
+        vec![
+            "browse_web",
+            "github_release",
+            "local_markdown_context",
+            "todoist_tasks"
+        ]
+    );
+    let results = model.tool_results();
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].1.contains("Juju 3.6.5 is out"));
+}
+
+#[tokio::test]
+async fn surfaces_error_when_script_runs_out() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let model = ScriptedModel::new([Turn::ToolCalls(vec![("todoist_tasks", json!({}))])]);
+
+    let agent = Agent::with_model(pipeline_config(&server, &glean_dir), model)
+        .expect("Failed to build agent");
+    let err = agent.prompt().await.expect_err("Expected prompt to fail");
+
+    assert!(err.to_string().contains("script exhausted"));
+}
+
+#[tokio::test]
+async fn records_every_tool_call_in_audit_log() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+    let audit_dir = tempdir().expect("Failed to create tempdir");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.audit = AuditConfig {
+        dir: Some(audit_dir.path().to_string_lossy().to_string()),
+        max_chars: Some(40),
+    };
+
+    let release_url = format!("{}/release", server.uri());
+    let model = ScriptedModel::new([
+        Turn::ToolCalls(vec![
+            ("browse_web", json!({ "url": release_url })),
+            (
+                "discourse_fetch",
+                json!({ "url": format!("{}/not-a-topic", server.uri()) }),
+            ),
+        ]),
+        Turn::Text("Draft".to_string()),
+    ]);
+
+    let agent = Agent::with_model(config, model).expect("Failed to build agent");
+    agent.prompt().await.expect("Prompt failed");
+
+    let files = std::fs::read_dir(audit_dir.path())
+        .expect("Failed to read audit dir")
+        .map(|e| e.expect("Bad dir entry").path())
+        .collect::<Vec<_>>();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].extension().is_some_and(|ext| ext == "jsonl"));
+
+    let mut records = std::fs::read_to_string(&files[0])
+        .expect("Failed to read audit log")
+        .lines()
+        .map(|line| serde_json::from_str::<AuditRecord>(line).expect("Invalid audit record"))
+        .collect::<Vec<_>>();
+    records.sort_by(|a, b| a.tool.cmp(&b.tool));
+    assert_eq!(records.len(), 2);
+
+    let web = &records[0];
+    assert_eq!(web.tool, "browse_web");
+    assert_eq!(web.args, json!({ "url": release_url }));
+    assert!(web.output.as_deref().unwrap().contains("Pebble 1.27.0"));
+    assert!(web.truncated);
+    assert!(web.error.is_none());
+
+    let discourse = &records[1];
+    assert_eq!(discourse.tool, "discourse_fetch");
+    assert!(discourse.output.is_none());
+    assert!(discourse
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("Not a Discourse topic URL"));
+}
+
+#[tokio::test]
+async fn self_correction_turn_fixes_format_violations() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.validate.self_correct = true;
+
+    let corrected = "### 🪨 Pebble [1.27.0](https://github.com/canonical/pebble/releases/tag/v1.27.0)\n\nAll done.\n\n## ✏️ Editor Review Notes\n";
+    let model = ScriptedModel::new([
+        Turn::Text("## 💻 Tech Updates\n\n### 🪨 Pebble [v1.27.0](https://github.com/canonical/pebble/releases/tag/v1.27.0)\n\nAll done.".to_string()),
+        Turn::Text(corrected.to_string()),
+    ]);
+
+    let agent = Agent::with_model(config, model.clone()).expect("Failed to build agent");
+    let response = agent.prompt().await.expect("Prompt failed");
+
+    assert_eq!(response, corrected);
+    assert_eq!(model.request_count(), 2);
+    let correction = model.last_prompt();
+    assert!(correction.contains("Tech Updates"));
+    assert!(correction.contains("strip the `v` prefix"));
+    assert!(correction.contains("Editor Review Notes"));
+}
+
+#[tokio::test]
+async fn prompts_with_profile_template() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+    let template = glean_dir.path().join("foundations.prompt.md");
+    std::fs::write(&template, "Draft the Foundations digest for {{audience}}.")
+        .expect("Failed to write template");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.prompt.template = Some(template.to_string_lossy().to_string());
+    config.prompt.audience = Some("the Foundations team".to_string());
+    let model = ScriptedModel::new([Turn::Text("Done.".to_string())]);
+
+    let agent = Agent::with_model(config, model.clone()).expect("Failed to build agent");
+    agent.prompt().await.expect("Prompt failed");
+
+    let prompt = model.last_prompt();
+    assert!(prompt.starts_with("Draft the Foundations digest for the Foundations team."));
+    assert!(!prompt.contains("# Role & Audience"));
+}
+
+#[tokio::test]
+async fn fails_to_build_with_missing_prompt_template() {
+    let server = MockServer::start().await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.prompt.template = Some("/does/not/exist.md".to_string());
+
+    let err = Agent::with_model(config, ScriptedModel::new([]))
+        .err()
+        .expect("Expected agent construction to fail");
+
+    assert!(err
+        .to_string()
+        .contains("Reading prompt template /does/not/exist.md"));
+}
+
+#[tokio::test]
+async fn fails_to_build_with_unknown_template_variable() {
+    let server = MockServer::start().await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+    let template = glean_dir.path().join("digest.prompt.md");
+    std::fs::write(&template, "Digest for {{team}}").expect("Failed to write template");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.prompt.template = Some(template.to_string_lossy().to_string());
+
+    let err = Agent::with_model(config, ScriptedModel::new([]))
+        .err()
+        .expect("Expected agent construction to fail");
+
+    assert!(format!("{:#}", err).contains("unknown variable `{{team}}`"));
+}
+
+#[tokio::test]
+async fn skips_self_correction_when_disabled() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let model = ScriptedModel::new([Turn::Text("## 💻 Tech Updates".to_string())]);
+
+    let agent = Agent::with_model(pipeline_config(&server, &glean_dir), model.clone())
+        .expect("Failed to build agent");
+    let response = agent.prompt().await.expect("Prompt failed");
+
+    assert_eq!(response, "## 💻 Tech Updates");
+    assert_eq!(model.request_count(), 1);
+}
+
+#[tokio::test]
+async fn appends_broken_links_to_editor_notes() {
+    let server = MockServer::start().await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    Mock::given(method("HEAD"))
+        .and(path("/release"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.links.enabled = true;
+
+    let draft = format!(
+        "### 🪨 Pebble [1.27.0]({uri}/release)\n\nSee the [docs]({uri}/missing-docs).\n\n## ✏️ Editor Review Notes\n",
+        uri = server.uri()
+    );
+    let model = ScriptedModel::new([Turn::Text(draft)]);
+
+    let agent = Agent::with_model(config, model).expect("Failed to build agent");
+    let response = agent.prompt().await.expect("Prompt failed");
+
+    assert!(response.ends_with(&format!(
+        "## ✏️ Editor Review Notes\n\n### 🔗 Links to verify\n- [ ] [Pebble 1.27.0] — {}/missing-docs could not be reached (HTTP 404)\n",
+        server.uri()
+    )));
+}
+
+#[tokio::test]
+async fn prefetches_task_links_into_the_prompt() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [
+                {
+                    "id": "1",
+                    "content": format!("Pebble 1.27.0 released {}/release", server.uri()),
+                    "description": format!("Announcement: {}/t/juju-365/42", server.uri()),
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false
+                }
+            ],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.prefetch.enabled = true;
+    let model = ScriptedModel::new([Turn::Text("### 🪨 Pebble 1.27.0\n\nDone.".to_string())]);
+
+    let agent = Agent::with_model(config, model.clone()).expect("Failed to build agent");
+    agent.prompt().await.expect("Prompt failed");
+
+    let prompt = model.last_prompt();
+    assert!(prompt.contains("# Prefetched Sources"));
+    assert!(prompt.contains(&format!("## {}/release\n\nTool: browse_web", server.uri())));
+    assert!(prompt.contains("Adds a syslog log target."));
+    assert!(prompt.contains(&format!(
+        "## {}/t/juju-365/42\n\nTool: discourse_fetch",
+        server.uri()
+    )));
+    assert!(prompt.contains("Juju 3.6.5 is out"));
+}
+
+#[tokio::test]
+async fn prefetches_from_a_manifest_without_listing_tasks_again() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [
+                {
+                    "id": "1",
+                    "content": format!("Pebble 1.27.0 released {}/release", server.uri()),
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false
+                }
+            ],
+            "next_cursor": null
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    mount_services(&server).await;
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let mut config = pipeline_config(&server, &glean_dir);
+    config.prefetch.enabled = true;
+    let model = ScriptedModel::new([Turn::Text("### 🪨 Pebble 1.27.0\n\nDone.".to_string())]);
+
+    let agent = Agent::with_model(config, model.clone()).expect("Failed to build agent");
+    let links = agent.manifest().await.expect("Manifest failed");
+    assert_eq!(links.len(), 1);
+    agent
+        .prompt_with_manifest(Some(links))
+        .await
+        .expect("Prompt failed");
+
+    assert!(model.last_prompt().contains("Adds a syslog log target."));
+}
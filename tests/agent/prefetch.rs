@@ -0,0 +1,188 @@
+use newsagent::agent::prefetch::{render_prefetched, PrefetchConfig, PrefetchedSource, Prefetcher};
+use newsagent::tools::github::{GithubConfig, GithubReleaseTool};
+use newsagent::tools::manifest::{LinkClassifier, ManifestLink};
+use newsagent::tools::todoist::{TodoistConfig, TodoistTasksTool};
+use newsagent::tools::web::{WebConfig, WebReadabilityTool};
+use std::time::{Duration, Instant};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const PAGE: &str = "<html><head><title>Release</title></head><body><article><p>Adds a syslog log target.</p></article></body></html>";
+
+fn prefetcher(server: &MockServer, web: WebConfig, concurrency: usize) -> Prefetcher {
+    let todoist = TodoistTasksTool::new(TodoistConfig {
+        api_token: "todo-token".to_string(),
+        project_id: "proj-1".to_string(),
+        project_section: None,
+        base_url: server.uri(),
+    })
+    .expect("Failed to create Todoist tool");
+    Prefetcher::new(
+        &PrefetchConfig {
+            enabled: true,
+            concurrency: Some(concurrency),
+            ..PrefetchConfig::default()
+        },
+        todoist,
+        None,
+        WebReadabilityTool::new(web).expect("Failed to create web tool"),
+        GithubReleaseTool::new(GithubConfig::default(), 8000).expect("Failed to create tool"),
+        None,
+    )
+}
+
+fn manifest(urls: &[String]) -> Vec<ManifestLink> {
+    let classifier = LinkClassifier::default();
+    urls.iter().map(|url| classifier.classify(url)).collect()
+}
+
+#[tokio::test]
+async fn fetches_links_concurrently_in_manifest_order() {
+    let server = MockServer::start().await;
+    for page in ["a", "b", "c"] {
+        Mock::given(method("GET"))
+            .and(path(format!("/{page}")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(PAGE)
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let urls: Vec<String> = ["a", "b", "missing", "c"]
+        .iter()
+        .map(|p| format!("{}/{}", server.uri(), p))
+        .collect();
+    let start = Instant::now();
+    let sources = prefetcher(&server, WebConfig::default(), 4)
+        .fetch_all(&manifest(&urls))
+        .await;
+
+    assert!(start.elapsed() < Duration::from_millis(1400));
+    let fetched: Vec<_> = sources.iter().map(|s| s.url.clone()).collect();
+    assert_eq!(fetched, urls);
+    assert!(sources.iter().all(|s| s.tool == "browse_web"));
+    assert!(sources[0]
+        .content
+        .as_ref()
+        .is_ok_and(|json| json.contains("Adds a syslog log target.")));
+    assert!(sources[2].content.is_err());
+
+    let rendered = render_prefetched(&sources, 40_000);
+    assert!(!rendered.contains("\"links\""));
+    assert!(rendered.starts_with("# Prefetched Sources"));
+    assert!(rendered.contains(&format!(
+        "## {}/missing\n\nTool: browse_web\n\nFetch failed:",
+        server.uri()
+    )));
+}
+
+#[tokio::test]
+async fn spaces_out_requests_to_the_same_host() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(PAGE))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let urls: Vec<String> = ["a", "b", "c"]
+        .iter()
+        .map(|p| format!("{}/{}", server.uri(), p))
+        .collect();
+    let web = WebConfig {
+        min_interval_ms: Some(200),
+        ..WebConfig::default()
+    };
+    let start = Instant::now();
+    let sources = prefetcher(&server, web, 3)
+        .fetch_all(&manifest(&urls))
+        .await;
+
+    assert!(start.elapsed() >= Duration::from_millis(400));
+    assert!(sources.iter().all(|s| s.content.is_ok()));
+}
+
+#[tokio::test]
+async fn shares_the_host_limiter_with_the_web_tool() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let web = WebReadabilityTool::new(WebConfig {
+        min_interval_ms: Some(300),
+        ..WebConfig::default()
+    })
+    .expect("Failed to create web tool");
+    let github = GithubReleaseTool::new(
+        GithubConfig {
+            token: None,
+            api_url: server.uri(),
+        },
+        8000,
+    )
+    .expect("Failed to create tool");
+    let todoist = TodoistTasksTool::new(TodoistConfig {
+        api_token: "todo-token".to_string(),
+        project_id: "proj-1".to_string(),
+        project_section: None,
+        base_url: server.uri(),
+    })
+    .expect("Failed to create Todoist tool");
+    let prefetcher = Prefetcher::new(
+        &PrefetchConfig::default(),
+        todoist,
+        None,
+        web.clone(),
+        github,
+        None,
+    );
+
+    // Two earlier `browse_web` calls to github.com push the next free slot 600ms out.
+    let start = Instant::now();
+    web.limiter().wait("github.com").await;
+    web.limiter().wait("github.com").await;
+    prefetcher
+        .fetch_all(&manifest(&[
+            "https://github.com/canonical/pebble/releases/tag/v1.27.0".to_string(),
+        ]))
+        .await;
+
+    assert!(start.elapsed() >= Duration::from_millis(550));
+}
+
+#[test]
+fn renders_nothing_without_sources() {
+    assert_eq!(render_prefetched(&[], 40_000), "");
+    assert_eq!(PrefetchConfig::default().effective_concurrency(), 4);
+    assert_eq!(PrefetchConfig::default().effective_max_chars(), 40_000);
+}
+
+#[test]
+fn keeps_prefetched_content_within_the_budget() {
+    let source = |name: &str, len: usize| PrefetchedSource {
+        url: format!("https://example.org/{name}"),
+        tool: "browse_web",
+        content: Ok("x".repeat(len)),
+    };
+    let sources = [source("a", 600), source("b", 1000), source("c", 600)];
+
+    let rendered = render_prefetched(&sources, 1200);
+
+    let sections: Vec<&str> = rendered.split("\n## ").skip(1).collect();
+    assert!(sections[0].contains(&"x".repeat(600)));
+    assert!(sections[1].contains(&format!("```json\n{}\n```", "x".repeat(600))));
+    assert!(sections[1].contains("Cut short to fit the prefetch budget"));
+    assert!(!sections[2].contains("xxx"));
+    assert!(sections[2].contains("Not included: the prefetch budget is used up"));
+}
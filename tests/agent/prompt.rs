@@ -1,4 +1,7 @@
-use newsagent::agent::prompt::{build_initial_prompt, PROMPT};
+use newsagent::agent::prompt::{
+    build_initial_prompt, build_prompt, check_template, load_prompt, PromptConfig, PromptVars,
+    DEFAULT_AUDIENCE, PROMPT,
+};
 
 #[test]
 fn prompt_contains_key_sections() {
@@ -16,10 +19,83 @@ fn prompt_contains_browse_web_instruction() {
 }
 
 #[test]
-fn build_initial_prompt_includes_base_prompt() {
+fn build_initial_prompt_renders_base_prompt() {
     let output = build_initial_prompt(None, &[], &[]);
 
-    assert!(output.starts_with(PROMPT));
+    assert!(output.contains("# Role & Audience"));
+    assert!(output.contains(&format!("The audience is {}.", DEFAULT_AUDIENCE)));
+    assert!(!output.contains("{{"));
+}
+
+#[test]
+fn built_in_prompt_is_a_valid_template() {
+    check_template(PROMPT).expect("Built-in prompt should be a valid template");
+}
+
+#[test]
+fn build_prompt_substitutes_variables() {
+    let hosts = vec!["discourse.ubuntu.com".to_string()];
+    let lists = vec!["ubuntu-devel".to_string(), "juju".to_string()];
+    let template = "{{ month }} for {{audience}}.\nSection: {{todoist_section}}\nHosts: {{discourse_hosts}}\nLists: {{mailing_lists}}";
+
+    let output = build_prompt(
+        template,
+        &PromptVars {
+            section: None,
+            discourse_hosts: &hosts,
+            discourse_sources: &[],
+            mailing_list_names: &lists,
+            month: "October 2026".to_string(),
+            audience: "the Foundations team",
+        },
+    );
+
+    assert!(output.starts_with(
+        "October 2026 for the Foundations team.\nSection: (all)\nHosts: discourse.ubuntu.com\nLists: ubuntu-devel, juju"
+    ));
+    assert!(output.contains("ALWAYS use discourse_fetch"));
+}
+
+#[test]
+fn check_template_rejects_unknown_variables() {
+    let err = check_template("# Digest\n\nWritten for {{ readers }}.")
+        .expect_err("Expected unknown variable to fail");
+
+    assert_eq!(
+        err.to_string(),
+        "line 3: unknown variable `{{readers}}` (expected one of: audience, discourse_hosts, mailing_lists, month, todoist_section)"
+    );
+}
+
+#[test]
+fn check_template_rejects_unclosed_placeholder() {
+    let err = check_template("Hello {{month").expect_err("Expected unclosed placeholder to fail");
+
+    assert!(err.to_string().contains("unclosed `{{`"));
+}
+
+#[test]
+fn load_prompt_reads_and_checks_template_file() {
+    let dir = tempfile::tempdir().expect("Failed to create tempdir");
+    let good = dir.path().join("good.md");
+    let bad = dir.path().join("bad.md");
+    std::fs::write(&good, "Digest for {{month}}").expect("Failed to write template");
+    std::fs::write(&bad, "Digest for {{week}}").expect("Failed to write template");
+
+    assert_eq!(load_prompt(&PromptConfig::default()).unwrap(), PROMPT);
+    let config = PromptConfig {
+        template: Some(good.to_string_lossy().to_string()),
+        ..PromptConfig::default()
+    };
+    assert_eq!(load_prompt(&config).unwrap(), "Digest for {{month}}");
+
+    let config = PromptConfig {
+        template: Some(bad.to_string_lossy().to_string()),
+        ..PromptConfig::default()
+    };
+    let err = load_prompt(&config).expect_err("Expected invalid template to fail");
+    assert!(err.to_string().starts_with("Invalid prompt template"));
+    assert!(format!("{:#}", err).contains("unknown variable `{{week}}`"));
 }
 
 #[test]
@@ -74,3 +150,19 @@ fn build_initial_prompt_omits_mailing_list_hint_when_no_lists() {
 
     assert!(!output.contains("MUST call mailing_list_threads"));
 }
+
+#[test]
+fn build_prompt_includes_discourse_recent_hint_when_sources_present() {
+    let sources = vec!["discourse.ubuntu.com c/announcements/41".to_string()];
+    let output = build_prompt(
+        "Base",
+        &PromptVars {
+            discourse_sources: &sources,
+            ..PromptVars::default()
+        },
+    );
+
+    assert!(output.contains("MUST call discourse_recent"));
+    assert!(output.contains("discourse.ubuntu.com c/announcements/41"));
+    assert!(!build_initial_prompt(None, &[], &[]).contains("MUST call discourse_recent"));
+}
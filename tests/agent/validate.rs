@@ -0,0 +1,212 @@
+use newsagent::agent::prompt::PROMPT;
+use newsagent::agent::validate::{
+    allowed_emoji, correction_prompt, validate, validate_with_emoji, ViolationKind,
+};
+
+const NOTES: &str = "\n---\n\n## ✏️ Editor Review Notes\n\n### 🔗 Links to verify\n- [ ] [Pebble] — check the docs link\n";
+
+fn kinds(markdown: &str) -> Vec<ViolationKind> {
+    validate(markdown).into_iter().map(|v| v.kind).collect()
+}
+
+#[test]
+fn accepts_prompt_example_entry() {
+    let start = PROMPT.find("### 🪨 Pebble").expect("Example entry missing");
+    let example = &PROMPT[start..];
+    let end = example.find("```").expect("Example fence missing");
+    let draft = format!("{}{}", &example[..end], NOTES);
+
+    assert_eq!(validate(&draft), vec![]);
+}
+
+#[test]
+fn flags_tech_updates_heading_and_missing_notes() {
+    let draft = "## 💻 Tech Updates\n\n### 🪨 Pebble [1.27.0](https://github.com/canonical/pebble/releases/tag/v1.27.0)\n\nText.\n";
+
+    assert_eq!(
+        kinds(draft),
+        vec![
+            ViolationKind::TechUpdatesHeading,
+            ViolationKind::MissingEditorNotes
+        ]
+    );
+}
+
+#[test]
+fn flags_bad_headings_and_unknown_emoji() {
+    let draft = format!(
+        "## 🪨 Pebble\n\n###🪨 Pebble\n\n### 🦄 Unicorn [1.0](https://github.com/u/u/releases/tag/1.0)\n\n### 🚀\n\n#### Sub-heading is fine\n{}",
+        NOTES
+    );
+
+    let violations = validate(&draft);
+    assert_eq!(
+        violations
+            .iter()
+            .map(|v| (v.line, v.kind))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, ViolationKind::BadHeading),
+            (3, ViolationKind::BadHeading),
+            (5, ViolationKind::UnknownEmoji),
+            (7, ViolationKind::BadHeading),
+        ]
+    );
+}
+
+#[test]
+fn accepts_headings_without_emoji_or_with_variation_selector_dropped() {
+    let draft = format!(
+        "### Chisel [1.0.0](https://github.com/canonical/chisel/releases/tag/v1.0.0)\n\n### ℹ Migration to Juju Terraform Provider 1.0\n{}",
+        NOTES
+    );
+
+    assert_eq!(validate(&draft), vec![]);
+}
+
+#[test]
+fn flags_v_prefixed_versions_in_link_text_only() {
+    let draft = format!(
+        "### 🚀 Juju [v3.6.9](https://github.com/juju/juju/releases/tag/v3.6.9)\n\nSee [3.6.9](https://github.com/juju/juju/releases/tag/v3.6.9) and `v3.6.9`.\n{}",
+        NOTES
+    );
+
+    let violations = validate(&draft);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ViolationKind::VersionPrefix);
+    assert_eq!(violations[0].line, 1);
+}
+
+#[test]
+fn flags_american_spellings_outside_code_and_urls() {
+    let draft = format!(
+        "### 🪄 Charmcraft [4.1.0](https://github.com/canonical/charmcraft/releases/tag/4.1.0)\n\nThis changes the default Behavior and helps organizations. The `color` flag lives at [docs](https://example.org/organization/color).\n{}",
+        NOTES
+    );
+
+    let violations = validate(&draft)
+        .into_iter()
+        .filter(|v| v.kind == ViolationKind::AmericanSpelling)
+        .map(|v| v.message)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        violations,
+        vec![
+            "use British spelling \"behaviour\" instead of \"behavior\"",
+            "use British spelling \"organisations\" instead of \"organizations\"",
+        ]
+    );
+}
+
+#[test]
+fn flags_emoji_in_body_but_allows_warnings_and_notes() {
+    let draft = format!(
+        "### 🚀 Juju [4.0.1](https://github.com/juju/juju/releases/tag/v4.0.1)\n\n⚠️ These releases contain important security fixes ⚠️\n\nDocs: [⚠️ link not found]\n\nGreat work 🎉\n{}",
+        NOTES
+    );
+
+    let violations = validate(&draft);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ViolationKind::EmojiInBody);
+    assert_eq!(violations[0].line, 7);
+}
+
+#[test]
+fn allows_symbols_without_emoji_presentation_in_body() {
+    let draft = format!(
+        "### 🚀 Juju [4.0.1](https://github.com/juju/juju/releases/tag/v4.0.1)\n\nPress ⌘K to search ★ and ✓ the results.\n\nNow ☁️ native ✅\n{}",
+        NOTES
+    );
+
+    let violations = validate(&draft);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ViolationKind::EmojiInBody);
+    assert_eq!(violations[0].message, "emoji ☁ used in body text");
+}
+
+#[test]
+fn flags_placeholder_urls() {
+    let draft = format!(
+        "### 📦 Snapcraft [8.14.0](https://github.com/canonical/snapcraft/releases/tag/8.14.0)\n\nSee the [release notes](https://example.com/notes), the [docs](url) and [more](<link>).\n{}",
+        NOTES
+    );
+
+    assert_eq!(
+        kinds(&draft),
+        vec![
+            ViolationKind::PlaceholderUrl,
+            ViolationKind::PlaceholderUrl,
+            ViolationKind::PlaceholderUrl,
+        ]
+    );
+}
+
+#[test]
+fn accepts_relative_and_mailto_links() {
+    let draft = format!(
+        "### 📦 Snapcraft [8.14.0](https://github.com/canonical/snapcraft/releases/tag/8.14.0)\n\nSee the [docs](/relative/path) or [mail](mailto:a@b.c), not [this]() or [that](#).\n{}",
+        NOTES
+    );
+
+    assert_eq!(
+        kinds(&draft),
+        vec![ViolationKind::PlaceholderUrl, ViolationKind::PlaceholderUrl]
+    );
+}
+
+#[test]
+fn ignores_fenced_code_blocks() {
+    let draft = format!(
+        "### 🧪 Testing [1.0](https://github.com/canonical/t/releases/tag/1.0)\n\n```\n## 💻 Tech Updates\ncolor = 🎉\n```\n{}",
+        NOTES
+    );
+
+    assert_eq!(validate(&draft), vec![]);
+}
+
+#[test]
+fn correction_prompt_lists_violations() {
+    let violations = validate("## 💻 Tech Updates\n");
+    let prompt = correction_prompt(&violations);
+
+    assert!(prompt.contains("complete corrected draft"));
+    assert!(prompt.contains("- line 1: remove the `## 💻 Tech Updates` heading"));
+    assert!(prompt.contains("- missing the trailing `## ✏️ Editor Review Notes` section"));
+}
+
+#[test]
+fn accepts_emoji_from_a_custom_template() {
+    let template = "Use 🦀 for Rust crates and 🛠️ for tooling, and 👩‍💻 for people.";
+    let emoji = allowed_emoji(template);
+    assert_eq!(emoji, ["🦀", "🛠️", "👩‍💻"]);
+
+    let draft = format!(
+        "### 🦀 Serde [1.0.200](https://github.com/serde-rs/serde/releases/tag/v1.0.200)\n\nText.\n\n### 🚀 Pebble [1.27.0](https://github.com/canonical/pebble/releases/tag/v1.27.0)\n\nText.\n{}",
+        NOTES
+    );
+    let kinds: Vec<_> = validate_with_emoji(&draft, &emoji)
+        .into_iter()
+        .map(|v| (v.line, v.kind))
+        .collect();
+    assert_eq!(kinds, vec![(5, ViolationKind::UnknownEmoji)]);
+
+    assert_eq!(allowed_emoji(PROMPT).len(), 25);
+}
+
+#[test]
+fn accepts_compare_urls_and_issue_references() {
+    let draft = format!(
+        "### 📦 Snapcraft [8.14.0](https://github.com/canonical/snapcraft/releases/tag/8.14.0)\n\n#123 is fixed; see the [diff](https://github.com/canonical/snapcraft/compare/8.13.0...8.14.0) and [your-charm](https://github.com/your-org/your-charm).\n{}",
+        NOTES
+    );
+    assert_eq!(kinds(&draft), vec![]);
+
+    let draft = format!(
+        "### 📦 Snapcraft [8.14.0](https://github.com/canonical/snapcraft/releases/tag/8.14.0)\n\nSee [this](https://github.com/canonical/.../releases) and [that](https://github.com/<owner>/snapcraft).\n{}",
+        NOTES
+    );
+    assert_eq!(
+        kinds(&draft),
+        vec![ViolationKind::PlaceholderUrl, ViolationKind::PlaceholderUrl]
+    );
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use common::with_newsagent_env;
+use newsagent::cache::{fetch_bytes, CacheConfig, HttpCache};
+use newsagent::tools::web::{WebConfig, WebReadabilityArgs, WebReadabilityTool};
+use rig::tool::Tool;
+use tempfile::{tempdir, TempDir};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn cache_in(dir: &TempDir, ttl_secs: Option<u64>, offline: bool) -> HttpCache {
+    HttpCache::new(CacheConfig {
+        dir: Some(dir.path().to_string_lossy().to_string()),
+        ttl_secs,
+        offline,
+    })
+    .expect("Failed to create cache")
+    .expect("Cache should be enabled")
+}
+
+async fn get(cache: Option<&HttpCache>, url: &str) -> anyhow::Result<Vec<u8>> {
+    fetch_bytes(cache, reqwest::Client::new().get(url), url).await
+}
+
+#[test]
+fn config_parses_cache_settings() {
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_CACHE_DIR", "/tmp/newsagent-cache"),
+        ("NEWSAGENT_CACHE_TTL_SECS", "600"),
+        ("NEWSAGENT_CACHE_OFFLINE", "yes"),
+    ]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<CacheConfig>()
+        .expect("Failed to parse CacheConfig from env");
+
+    assert_eq!(config.dir.as_deref(), Some("/tmp/newsagent-cache"));
+    assert_eq!(config.ttl_secs, Some(600));
+    assert!(config.offline);
+}
+
+#[test]
+fn cache_is_disabled_without_dir_and_offline_requires_one() {
+    assert!(HttpCache::new(CacheConfig::default())
+        .expect("Failed to create cache")
+        .is_none());
+
+    let err = HttpCache::new(CacheConfig {
+        offline: true,
+        ..CacheConfig::default()
+    })
+    .expect_err("Expected offline without a dir to fail");
+    assert!(err.to_string().contains("NEWSAGENT_CACHE_DIR"));
+}
+
+#[tokio::test]
+async fn fresh_entry_is_served_without_a_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("Failed to create tempdir");
+    let cache = cache_in(&dir, None, false);
+    let url = format!("{}/page", server.uri());
+
+    assert_eq!(get(Some(&cache), &url).await.unwrap(), b"hello");
+    assert_eq!(get(Some(&cache), &url).await.unwrap(), b"hello");
+}
+
+#[tokio::test]
+async fn stale_entry_is_revalidated_with_etag() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_string("hello"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("Failed to create tempdir");
+    let cache = cache_in(&dir, Some(0), false);
+    let url = format!("{}/page", server.uri());
+
+    assert_eq!(get(Some(&cache), &url).await.unwrap(), b"hello");
+    assert_eq!(get(Some(&cache), &url).await.unwrap(), b"hello");
+}
+
+#[tokio::test]
+async fn offline_mode_serves_cached_bodies_only() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("Failed to create tempdir");
+    let url = format!("{}/page", server.uri());
+    get(Some(&cache_in(&dir, Some(0), false)), &url)
+        .await
+        .expect("Priming fetch failed");
+
+    let offline = cache_in(&dir, Some(0), true);
+    assert_eq!(get(Some(&offline), &url).await.unwrap(), b"hello");
+
+    let missing = format!("{}/other", server.uri());
+    let err = get(Some(&offline), &missing)
+        .await
+        .expect_err("Expected uncached URL to fail offline");
+    assert!(err.to_string().contains("not cached"));
+}
+
+#[tokio::test]
+async fn web_tool_reads_through_cache() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            "<html><head><title>Example</title></head><body><article><p>Hello world</p></article></body></html>",
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("Failed to create tempdir");
+    let tool = WebReadabilityTool::new(WebConfig {
+        allowlist: Some("127.0.0.1".to_string()),
+        ..WebConfig::default()
+    })
+    .expect("Failed to create web tool")
+    .with_cache(Some(cache_in(&dir, None, false)));
+
+    let url = format!("{}/page", server.uri());
+    for _ in 0..2 {
+        let output = tool
+            .call(WebReadabilityArgs { url: url.clone() })
+            .await
+            .expect("Web tool call failed");
+        assert_eq!(output.title, "Example");
+    }
+}
+
+#[tokio::test]
+async fn cache_hits_skip_the_rate_limit_and_keep_the_charset() {
+    let server = MockServer::start().await;
+    let mut page = b"<html><head><title>Caf\xe9</title></head><body><article><p>".to_vec();
+    page.extend_from_slice(
+        "Déjà vu: the release notes for this month are long enough to count as the article."
+            .chars()
+            .map(|c| c as u8)
+            .collect::<Vec<u8>>()
+            .as_slice(),
+    );
+    page.extend_from_slice(b"</p></article></body></html>");
+    Mock::given(method("GET"))
+        .and(path("/latin1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/html; charset=ISO-8859-1")
+                .set_body_bytes(page),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("Failed to create tempdir");
+    let tool = WebReadabilityTool::new(WebConfig {
+        allowlist: Some("127.0.0.1".to_string()),
+        min_interval_ms: Some(2000),
+        ..WebConfig::default()
+    })
+    .expect("Failed to create web tool")
+    .with_cache(Some(cache_in(&dir, None, false)));
+
+    let url = format!("{}/latin1", server.uri());
+    let start = std::time::Instant::now();
+    for _ in 0..3 {
+        let output = tool
+            .call(WebReadabilityArgs { url: url.clone() })
+            .await
+            .expect("Web tool call failed");
+        assert_eq!(output.title, "Café");
+        assert!(output.text.contains("Déjà vu"), "{}", output.text);
+    }
+    assert!(start.elapsed() < std::time::Duration::from_millis(1000));
+}
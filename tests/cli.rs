@@ -0,0 +1,335 @@
+use clap::Parser;
+use newsagent::agent::audit::AuditConfig;
+use newsagent::agent::links::LinkCheckConfig;
+use newsagent::agent::prefetch::PrefetchConfig;
+use newsagent::agent::prompt::PromptConfig;
+use newsagent::agent::provider::LlmConfig;
+use newsagent::agent::validate::ValidateConfig;
+use newsagent::cache::CacheConfig;
+use newsagent::cli::{Cli, Command};
+use newsagent::config::{AppConfig, ConfigFile};
+use newsagent::output::OutputConfig;
+use newsagent::tools::discourse::DiscourseConfig;
+use newsagent::tools::github::GithubConfig;
+use newsagent::tools::glean::GleanConfig;
+use newsagent::tools::mailing_list::MailingListConfig;
+use newsagent::tools::todoist::TodoistConfig;
+use newsagent::tools::web::WebConfig;
+use std::path::Path;
+use tempfile::tempdir;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn test_config(todoist_base_url: &str, glean_dir: &str) -> AppConfig {
+    AppConfig {
+        gemini_api_key: Some("test-key".to_string()),
+        gemini_model: "test-model".to_string(),
+        profile: None,
+        llm: LlmConfig::default(),
+        todoist: TodoistConfig {
+            api_token: "todo-token".to_string(),
+            project_id: "proj-1".to_string(),
+            project_section: None,
+            base_url: todoist_base_url.to_string(),
+        },
+        glean: GleanConfig {
+            dir: glean_dir.to_string(),
+            filter: None,
+        },
+        web: WebConfig::default(),
+        github: GithubConfig::default(),
+        discourse: DiscourseConfig::default(),
+        mailing_list: MailingListConfig::default(),
+        output: OutputConfig::default(),
+        cache: CacheConfig::default(),
+        audit: AuditConfig::default(),
+        validate: ValidateConfig::default(),
+        links: LinkCheckConfig::default(),
+        prompt: PromptConfig::default(),
+        prefetch: PrefetchConfig::default(),
+    }
+}
+
+#[test]
+fn parses_subcommands() {
+    let cli = Cli::try_parse_from(["newsagent"]).expect("Failed to parse");
+    assert_eq!(cli.command, None);
+
+    let cli =
+        Cli::try_parse_from(["newsagent", "tasks", "--section", "Now"]).expect("Failed to parse");
+    assert_eq!(
+        cli.command,
+        Some(Command::Tasks {
+            section: Some("Now".to_string()),
+            links: false
+        })
+    );
+
+    let cli = Cli::try_parse_from(["newsagent", "fetch", "https://example.com", "--web"])
+        .expect("Failed to parse");
+    assert_eq!(
+        cli.command,
+        Some(Command::Fetch {
+            url: "https://example.com".to_string(),
+            web: true
+        })
+    );
+
+    let cli = Cli::try_parse_from(["newsagent", "draft", "--output", "out", "--archive"])
+        .expect("Failed to parse");
+    assert_eq!(
+        cli.command,
+        Some(Command::Draft {
+            output: Some("out".into()),
+            archive: true,
+            force: false,
+            self_correct: false,
+            check_links: false,
+            prefetch: false,
+            close_tasks: false,
+            move_tasks: false,
+            dry_run: false
+        })
+    );
+
+    let cli = Cli::try_parse_from(["newsagent", "draft", "--move-tasks", "--dry-run"])
+        .expect("Failed to parse");
+    assert!(matches!(
+        cli.command,
+        Some(Command::Draft {
+            close_tasks: false,
+            move_tasks: true,
+            dry_run: true,
+            ..
+        })
+    ));
+    assert!(Cli::try_parse_from(["newsagent", "draft", "--close-tasks", "--move-tasks"]).is_err());
+    assert!(Cli::try_parse_from(["newsagent", "draft", "--dry-run"]).is_err());
+
+    let cli = Cli::try_parse_from(["newsagent", "check-config"]).expect("Failed to parse");
+    assert_eq!(cli.command, Some(Command::CheckConfig));
+
+    let cli =
+        Cli::try_parse_from(["newsagent", "tasks", "--profile", "juju"]).expect("Failed to parse");
+    assert_eq!(cli.profile.as_deref(), Some("juju"));
+}
+
+#[test]
+fn rejects_fetch_without_url() {
+    assert!(Cli::try_parse_from(["newsagent", "fetch"]).is_err());
+}
+
+#[tokio::test]
+async fn tasks_prints_rendered_markdown() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/sections"))
+        .and(query_param("project_id", "proj-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .and(query_param("project_id", "proj-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [
+                {
+                    "id": "1",
+                    "content": "Task 1",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false
+                }
+            ],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    let cli = Cli {
+        config: None,
+        profile: None,
+        offline: false,
+        command: Some(Command::Tasks {
+            section: None,
+            links: false,
+        }),
+    };
+    let output = cli
+        .run(test_config(&server.uri(), "/does/not/exist"))
+        .await
+        .expect("tasks subcommand failed");
+
+    assert_eq!(output, "- [ ] Task 1");
+}
+
+#[tokio::test]
+async fn tasks_links_prints_link_manifest() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .and(query_param("project_id", "proj-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [
+                {
+                    "id": "1",
+                    "content": "https://github.com/canonical/pebble/releases/tag/v1.27.0",
+                    "description": "https://blog.example.com/post",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false
+                }
+            ],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    let mut config = test_config(&server.uri(), "/does/not/exist");
+    config.web.allowlist = Some("github.com".to_string());
+    let output = Cli::try_parse_from(["newsagent", "tasks", "--links"])
+        .expect("Failed to parse")
+        .run(config)
+        .await
+        .expect("tasks --links failed");
+
+    assert_eq!(
+        output,
+        "- https://github.com/canonical/pebble/releases/tag/v1.27.0 (github release, github_release)\n\
+         - https://blog.example.com/post (web, unsupported: blog.example.com is not in NEWSAGENT_WEB_ALLOWLIST)"
+    );
+}
+
+#[tokio::test]
+async fn check_config_reports_summary() {
+    let dir = tempdir().expect("Failed to create tempdir");
+    let cli = Cli {
+        config: None,
+        profile: None,
+        offline: false,
+        command: Some(Command::CheckConfig),
+    };
+
+    let output = cli
+        .run(test_config(
+            "https://api.todoist.com",
+            &dir.path().to_string_lossy(),
+        ))
+        .await
+        .expect("check-config subcommand failed");
+
+    assert!(output.starts_with("Configuration OK"));
+    assert!(output.contains("todoist project: proj-1 (section: (all))"));
+    assert!(output.contains("prompt template: (built-in)"));
+    assert!(output.contains("discourse instances: (none)"));
+}
+
+#[tokio::test]
+async fn check_config_fails_when_glean_dir_missing() {
+    let cli = Cli {
+        config: None,
+        profile: None,
+        offline: false,
+        command: Some(Command::CheckConfig),
+    };
+
+    let err = cli
+        .run(test_config("https://api.todoist.com", "/does/not/exist"))
+        .await
+        .expect_err("Expected check-config to fail");
+
+    assert!(err.to_string().contains("Glean directory not found"));
+}
+
+#[tokio::test]
+async fn check_config_fails_without_an_api_key() {
+    let dir = tempdir().expect("Failed to create tempdir");
+    let cli = Cli {
+        config: None,
+        profile: None,
+        offline: false,
+        command: Some(Command::CheckConfig),
+    };
+    let mut config = test_config("https://api.todoist.com", &dir.path().to_string_lossy());
+    config.gemini_api_key = None;
+
+    let err = cli
+        .run(config)
+        .await
+        .expect_err("Expected check-config to fail");
+
+    assert!(err.to_string().contains("NEWSAGENT_GEMINI_API_KEY"));
+}
+
+#[tokio::test]
+async fn check_config_lets_env_replace_file_mailing_lists() {
+    let dir = tempdir().expect("Failed to create tempdir");
+    let file = ConfigFile::parse(
+        Path::new("newsagent.toml"),
+        "[mailing_list]\nlists = [\"a\", \"b\"]\n",
+    )
+    .expect("Failed to parse config file");
+    let glean_dir = dir.path().to_string_lossy().into_owned();
+    let vars = [
+        ("NEWSAGENT_GEMINI_API_KEY", "test-key"),
+        ("NEWSAGENT_TODOIST_API_TOKEN", "todo-token"),
+        ("NEWSAGENT_TODOIST_PROJECT_ID", "proj-1"),
+        ("NEWSAGENT_GLEAN_DIR", glean_dir.as_str()),
+        ("NEWSAGENT_MAILING_LISTS", "c"),
+    ]
+    .map(|(k, v)| (k.to_string(), v.to_string()));
+    let config = AppConfig::from_layers(Some(&file), vars).expect("Failed to build config");
+    assert_eq!(config.mailing_list.lists, vec!["c"]);
+
+    let cli = Cli {
+        config: None,
+        profile: None,
+        offline: false,
+        command: Some(Command::CheckConfig),
+    };
+    let output = cli
+        .run(config)
+        .await
+        .expect("check-config subcommand failed");
+
+    assert!(output.contains("mailing lists: c\n"));
+}
+
+#[tokio::test]
+async fn validate_reports_format_violations() {
+    let dir = tempdir().expect("Failed to create tempdir");
+    let good = dir.path().join("good.md");
+    let bad = dir.path().join("bad.md");
+    std::fs::write(
+        &good,
+        "### 🪨 Pebble [1.27.0](https://github.com/canonical/pebble/releases/tag/v1.27.0)\n\n## ✏️ Editor Review Notes\n",
+    )
+    .expect("Failed to write draft");
+    std::fs::write(&bad, "## 💻 Tech Updates\n").expect("Failed to write draft");
+    let config = test_config("https://api.todoist.com", &dir.path().to_string_lossy());
+
+    let output = Cli::try_parse_from(["newsagent", "validate", &good.to_string_lossy()])
+        .expect("Failed to parse")
+        .run(config.clone())
+        .await
+        .expect("Validate failed");
+    assert!(output.ends_with("no format violations"));
+
+    let err = Cli::try_parse_from(["newsagent", "validate", &bad.to_string_lossy()])
+        .expect("Failed to parse")
+        .run(config)
+        .await
+        .expect_err("Expected violations");
+    let message = err.to_string();
+    assert!(message.contains("2 format violation(s)"));
+    assert!(message.contains("line 1: remove the `## 💻 Tech Updates` heading"));
+}
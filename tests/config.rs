@@ -1,7 +1,10 @@
 mod common;
 
 use common::with_newsagent_env;
-use newsagent::config::AppConfig;
+use newsagent::agent::provider::{model_name, LlmProvider};
+use newsagent::config::{AppConfig, ConfigFile};
+use newsagent::tools::mailing_list::ArchiveFormat;
+use std::path::Path;
 
 fn required_env_vars() -> Vec<(&'static str, &'static str)> {
     vec![
@@ -18,7 +21,7 @@ fn test_config_loads_valid_config() {
 
     let config = AppConfig::from_env().expect("Failed to parse config");
 
-    assert_eq!(config.gemini_api_key, "test_key");
+    assert_eq!(config.gemini_api_key.as_deref(), Some("test_key"));
     assert_eq!(config.todoist.api_token, "todo_token");
     assert_eq!(config.todoist.project_id, "12345");
     assert_eq!(config.glean.dir, "/tmp/glean");
@@ -54,3 +57,392 @@ fn test_config_missing_required_fields() {
     let config = AppConfig::from_env();
     assert!(config.is_err());
 }
+
+#[test]
+fn test_config_defaults_to_gemini_provider() {
+    let _guard = with_newsagent_env(required_env_vars());
+
+    let config = AppConfig::from_env().expect("Failed to parse config");
+
+    assert_eq!(config.llm.provider, LlmProvider::Gemini);
+    assert!(config.llm.model.is_none());
+    assert_eq!(
+        model_name(&config).expect("Failed to resolve model"),
+        "gemini-3.1-pro-preview"
+    );
+}
+
+#[test]
+fn test_config_with_llm_provider() {
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_TODOIST_API_TOKEN", "todo_token"),
+        ("NEWSAGENT_TODOIST_PROJECT_ID", "12345"),
+        ("NEWSAGENT_GLEAN_DIR", "/tmp/glean"),
+        ("NEWSAGENT_LLM_PROVIDER", "openai"),
+        ("NEWSAGENT_LLM_MODEL", "local-model"),
+        ("NEWSAGENT_LLM_BASE_URL", "http://localhost:8080/v1"),
+        ("NEWSAGENT_LLM_MAX_TOKENS", "4096"),
+    ]);
+
+    let config = AppConfig::from_env().expect("Failed to parse config");
+
+    assert!(config.gemini_api_key.is_none());
+    assert_eq!(config.llm.provider, LlmProvider::OpenAi);
+    assert_eq!(
+        config.llm.base_url.as_deref(),
+        Some("http://localhost:8080/v1")
+    );
+    assert_eq!(config.llm.max_tokens, Some(4096));
+    assert_eq!(
+        model_name(&config).expect("Failed to resolve model"),
+        "local-model"
+    );
+}
+
+#[test]
+fn test_config_rejects_unknown_llm_provider() {
+    let mut vars = required_env_vars();
+    vars.push(("NEWSAGENT_LLM_PROVIDER", "carrier-pigeon"));
+    let _guard = with_newsagent_env(vars);
+
+    let config = AppConfig::from_env();
+    assert!(config.is_err());
+}
+
+#[test]
+fn test_model_name_required_for_non_gemini_providers() {
+    let mut vars = required_env_vars();
+    vars.push(("NEWSAGENT_LLM_PROVIDER", "anthropic"));
+    let _guard = with_newsagent_env(vars);
+
+    let config = AppConfig::from_env().expect("Failed to parse config");
+
+    let err = model_name(&config).expect_err("Expected missing model error");
+    assert!(err
+        .to_string()
+        .contains("NEWSAGENT_LLM_MODEL must be set for the anthropic provider"));
+}
+
+const TEAM_CONFIG: &str = r#"
+gemini_model = "file-model"
+
+[todoist]
+api_token = "file-token"
+project_id = "file-project"
+
+[glean]
+dir = "/srv/glean"
+
+[web]
+allowlist = ["github.com", ".ubuntu.com"]
+max_chars = 4000
+
+[[discourse.instances]]
+host = "discourse.ubuntu.com"
+api_key = "secret"
+api_username = "newsbot"
+
+[[discourse.instances]]
+host = "discourse.charmhub.io"
+
+[mailing_list]
+base_url = "https://lists.example.org/archives"
+
+[[mailing_list.lists]]
+name = "ubuntu-devel"
+
+[[mailing_list.lists]]
+name = "juju"
+base_url = "https://lists.juju.example/archives"
+
+[[mailing_list.lists]]
+name = "devel@lists.example.org"
+base_url = "https://lists.example.org/archives"
+format = "hyperkitty"
+"#;
+
+fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_config_file_tables_map_to_settings() {
+    let file = ConfigFile::parse(Path::new("newsagent.toml"), TEAM_CONFIG)
+        .expect("Failed to parse config file");
+
+    let config = AppConfig::from_layers(Some(&file), Vec::new()).expect("Failed to build config");
+
+    assert_eq!(config.gemini_model, "file-model");
+    assert_eq!(config.todoist.api_token, "file-token");
+    assert_eq!(config.todoist.project_id, "file-project");
+    assert_eq!(config.glean.dir, "/srv/glean");
+    assert_eq!(
+        config.web.allowlist.as_deref(),
+        Some("github.com,.ubuntu.com")
+    );
+    assert_eq!(config.web.max_chars, Some(4000));
+
+    let instances = &config.discourse.instances;
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0].base_url, "discourse.ubuntu.com");
+    assert_eq!(instances[0].api_key.as_deref(), Some("secret"));
+    assert_eq!(instances[0].api_username.as_deref(), Some("newsbot"));
+    assert_eq!(instances[1].base_url, "discourse.charmhub.io");
+    assert!(instances[1].api_key.is_none());
+
+    assert_eq!(
+        config.mailing_list.lists,
+        vec!["ubuntu-devel", "juju", "devel@lists.example.org"]
+    );
+    assert_eq!(
+        config.mailing_list.effective_base_url(),
+        "https://lists.example.org/archives"
+    );
+    assert_eq!(
+        config
+            .mailing_list
+            .list_base_urls
+            .get("juju")
+            .map(String::as_str),
+        Some("https://lists.juju.example/archives")
+    );
+    assert!(!config
+        .mailing_list
+        .list_base_urls
+        .contains_key("ubuntu-devel"));
+    assert_eq!(
+        config.mailing_list.archive_for("devel@lists.example.org"),
+        (
+            "https://lists.example.org/archives",
+            ArchiveFormat::Hyperkitty
+        )
+    );
+    assert_eq!(
+        config.mailing_list.archive_for("juju").1,
+        ArchiveFormat::Pipermail
+    );
+}
+
+#[test]
+fn test_env_vars_override_config_file() {
+    let file = ConfigFile::parse(Path::new("newsagent.toml"), TEAM_CONFIG)
+        .expect("Failed to parse config file");
+
+    let config = AppConfig::from_layers(
+        Some(&file),
+        vars(&[
+            ("NEWSAGENT_TODOIST_API_TOKEN", "env-token"),
+            ("NEWSAGENT_WEB_MAX_CHARS", "100"),
+            (
+                "NEWSAGENT_DISCOURSE_INSTANCES",
+                "discourse.env.example=envkey",
+            ),
+            ("NEWSAGENT_MAILING_LISTS", "ubuntu-announce"),
+            ("UNRELATED", "ignored"),
+        ]),
+    )
+    .expect("Failed to build config");
+
+    assert_eq!(config.todoist.api_token, "env-token");
+    assert_eq!(config.todoist.project_id, "file-project");
+    assert_eq!(config.web.max_chars, Some(100));
+    assert_eq!(config.discourse.instances.len(), 1);
+    assert_eq!(
+        config.discourse.instances[0].base_url,
+        "discourse.env.example"
+    );
+    assert_eq!(config.mailing_list.lists, vec!["ubuntu-announce"]);
+    assert!(config.mailing_list.list_base_urls.is_empty());
+}
+
+#[test]
+fn test_config_file_accepts_flat_lists() {
+    let file = ConfigFile::parse(
+        Path::new("newsagent.toml"),
+        r#"
+        [todoist]
+        api_token = "t"
+        project_id = "p"
+        [glean]
+        dir = "/g"
+        [discourse]
+        instances = "discourse.ubuntu.com=key"
+        [mailing_list]
+        lists = ["ubuntu-devel", "ubuntu-release"]
+        [output]
+        archive = true
+        "#,
+    )
+    .expect("Failed to parse config file");
+
+    let config = AppConfig::from_layers(Some(&file), Vec::new()).expect("Failed to build config");
+
+    assert_eq!(
+        config.discourse.instances[0].api_key.as_deref(),
+        Some("key")
+    );
+    assert_eq!(
+        config.mailing_list.lists,
+        vec!["ubuntu-devel", "ubuntu-release"]
+    );
+    assert!(config.output.archive);
+}
+
+#[test]
+fn test_config_file_reports_parse_errors() {
+    let err = ConfigFile::parse(Path::new("team.toml"), "[todoist\n")
+        .expect_err("Expected invalid TOML to fail");
+
+    assert!(err.to_string().contains("team.toml"));
+}
+
+#[test]
+fn test_load_reads_config_file_from_env_path() {
+    let dir = tempfile::tempdir().expect("Failed to create tempdir");
+    let path = dir.path().join("team.toml");
+    std::fs::write(&path, TEAM_CONFIG).expect("Failed to write config file");
+    let path = path.to_string_lossy().to_string();
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_CONFIG_FILE", path.as_str()),
+        ("NEWSAGENT_GLEAN_DIR", "/env/glean"),
+    ]);
+
+    let config = AppConfig::load(None, None).expect("Failed to load config");
+
+    assert_eq!(config.todoist.api_token, "file-token");
+    assert_eq!(config.glean.dir, "/env/glean");
+}
+
+const PROFILES_CONFIG: &str = r#"
+profile = "foundations"
+
+[todoist]
+api_token = "shared-token"
+project_id = "shared-project"
+
+[glean]
+dir = "/srv/glean"
+
+[profiles.foundations]
+prompt_template = "prompts/foundations.md"
+
+[profiles.foundations.todoist]
+project_id = "foundations-project"
+project_section = "Next issue"
+
+[[profiles.foundations.mailing_list.lists]]
+name = "ubuntu-devel"
+
+[profiles.juju.todoist]
+project_id = "juju-project"
+
+[profiles.juju.glean]
+dir = "/srv/juju-glean"
+
+[[profiles.juju.discourse.instances]]
+host = "discourse.charmhub.io"
+"#;
+
+#[test]
+fn test_profile_defaults_to_file_setting() {
+    let file = ConfigFile::parse(Path::new("newsagent.toml"), PROFILES_CONFIG)
+        .expect("Failed to parse config file");
+
+    let config = AppConfig::from_layers(Some(&file), Vec::new()).expect("Failed to build config");
+
+    assert_eq!(file.profiles(), vec!["foundations", "juju"]);
+    assert_eq!(config.profile.as_deref(), Some("foundations"));
+    assert_eq!(config.todoist.api_token, "shared-token");
+    assert_eq!(config.todoist.project_id, "foundations-project");
+    assert_eq!(
+        config.todoist.project_section.as_deref(),
+        Some("Next issue")
+    );
+    assert_eq!(config.glean.dir, "/srv/glean");
+    assert_eq!(
+        config.prompt.template.as_deref(),
+        Some("prompts/foundations.md")
+    );
+    assert_eq!(config.mailing_list.lists, vec!["ubuntu-devel"]);
+    assert!(config.discourse.instances.is_empty());
+}
+
+#[test]
+fn test_profile_selected_by_env_var() {
+    let file = ConfigFile::parse(Path::new("newsagent.toml"), PROFILES_CONFIG)
+        .expect("Failed to parse config file");
+
+    let config = AppConfig::from_layers(Some(&file), vars(&[("NEWSAGENT_PROFILE", "juju")]))
+        .expect("Failed to build config");
+
+    assert_eq!(config.profile.as_deref(), Some("juju"));
+    assert_eq!(config.todoist.project_id, "juju-project");
+    assert!(config.todoist.project_section.is_none());
+    assert_eq!(config.glean.dir, "/srv/juju-glean");
+    assert!(config.prompt.template.is_none());
+    assert!(config.mailing_list.lists.is_empty());
+    assert_eq!(
+        config.discourse.instances[0].base_url,
+        "discourse.charmhub.io"
+    );
+}
+
+#[test]
+fn test_unknown_profile_lists_available_profiles() {
+    let file = ConfigFile::parse(Path::new("newsagent.toml"), PROFILES_CONFIG)
+        .expect("Failed to parse config file");
+
+    let err = AppConfig::from_layers(Some(&file), vars(&[("NEWSAGENT_PROFILE", "ubuntu")]))
+        .expect_err("Expected unknown profile to fail");
+
+    assert_eq!(
+        err.to_string(),
+        "Unknown profile `ubuntu` in newsagent.toml (available: foundations, juju)"
+    );
+}
+
+#[test]
+fn test_profile_requires_config_file() {
+    let err = AppConfig::from_layers(None, vars(&[("NEWSAGENT_PROFILE", "juju")]))
+        .expect_err("Expected profile without file to fail");
+
+    assert!(err.to_string().contains("no config file was found"));
+}
+
+#[test]
+fn test_load_selects_profile_argument() {
+    let dir = tempfile::tempdir().expect("Failed to create tempdir");
+    let path = dir.path().join("newsagent.toml");
+    std::fs::write(&path, PROFILES_CONFIG).expect("Failed to write config file");
+    let _guard = with_newsagent_env(vec![("NEWSAGENT_PROFILE", "foundations")]);
+
+    let config = AppConfig::load(Some(&path), Some("juju")).expect("Failed to load config");
+
+    assert_eq!(config.profile.as_deref(), Some("juju"));
+    assert_eq!(config.todoist.project_id, "juju-project");
+}
+
+#[test]
+fn test_config_file_expands_home_in_paths() {
+    let file = ConfigFile::parse(
+        Path::new("newsagent.toml"),
+        "[glean]\ndir = \"~/newsletter/archive\"\n\n[prompt]\ntemplate = \"prompts/~/tech.md\"\n",
+    )
+    .expect("Failed to parse config file");
+
+    let env: Vec<_> = required_env_vars()
+        .into_iter()
+        .filter(|(key, _)| *key != "NEWSAGENT_GLEAN_DIR")
+        .collect();
+    let config = AppConfig::from_layers(Some(&file), vars(&env)).expect("Failed to build config");
+
+    let home = std::env::var("HOME").expect("HOME is set");
+    assert_eq!(
+        Path::new(&config.glean.dir),
+        Path::new(&home).join("newsletter/archive")
+    );
+    assert_eq!(config.prompt.template.as_deref(), Some("prompts/~/tech.md"));
+}
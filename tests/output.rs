@@ -0,0 +1,156 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::with_newsagent_env;
+use newsagent::output::{DraftWriter, OutputConfig, OutputError};
+use newsagent::tools::glean::{GleanConfig, GleanTool};
+use std::fs;
+use tempfile::tempdir;
+
+fn october() -> chrono::DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap()
+}
+
+#[test]
+fn config_parses_output_settings() {
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_OUTPUT_DIR", "/tmp/drafts"),
+        ("NEWSAGENT_OUTPUT_PREFIX", "eng-updates"),
+        ("NEWSAGENT_OUTPUT_ARCHIVE", "true"),
+    ]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<OutputConfig>()
+        .expect("Failed to parse OutputConfig from env");
+
+    assert_eq!(config.dir.as_deref(), Some("/tmp/drafts"));
+    assert_eq!(config.effective_prefix(), "eng-updates");
+    assert!(config.archive);
+}
+
+#[test]
+fn config_defaults() {
+    let _guard = with_newsagent_env(vec![]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<OutputConfig>()
+        .expect("Failed to parse OutputConfig from env");
+
+    assert!(config.dir.is_none());
+    assert_eq!(config.effective_prefix(), "tech-updates");
+    assert!(!config.archive);
+}
+
+#[test]
+fn writes_month_stamped_file_into_directory() {
+    let dir = tempdir().expect("Failed to create tempdir");
+
+    let written = DraftWriter::new(dir.path(), "tech-updates")
+        .write("### 🪨 Pebble\n\n", october())
+        .expect("Failed to write draft");
+
+    assert_eq!(written.path, dir.path().join("tech-updates-2026-10.md"));
+    assert!(written.archived.is_none());
+    let contents = fs::read_to_string(&written.path).expect("Failed to read draft");
+    assert_eq!(contents, "### 🪨 Pebble\n");
+}
+
+#[test]
+fn writes_to_explicit_file_path() {
+    let dir = tempdir().expect("Failed to create tempdir");
+    let target = dir.path().join("nested/draft.md");
+
+    let written = DraftWriter::new(&target, "tech-updates")
+        .write("Draft", october())
+        .expect("Failed to write draft");
+
+    assert_eq!(written.path, target);
+    assert!(target.exists());
+}
+
+#[test]
+fn refuses_to_overwrite_without_force() {
+    let dir = tempdir().expect("Failed to create tempdir");
+    let existing = dir.path().join("tech-updates-2026-10.md");
+    fs::write(&existing, "Original").expect("Failed to write file");
+
+    let err = DraftWriter::new(dir.path(), "tech-updates")
+        .write("New draft", october())
+        .expect_err("Expected overwrite to be refused");
+
+    match err {
+        OutputError::AlreadyExists(path) => assert!(path.ends_with("tech-updates-2026-10.md")),
+        other => panic!("Unexpected error: {other:?}"),
+    }
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "Original");
+
+    DraftWriter::new(dir.path(), "tech-updates")
+        .force(true)
+        .write("New draft", october())
+        .expect("Forced write failed");
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "New draft\n");
+}
+
+#[test]
+fn archives_into_glean_directory() {
+    let out_dir = tempdir().expect("Failed to create tempdir");
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+
+    let written = DraftWriter::new(out_dir.path(), "tech-updates")
+        .archive_to(glean_dir.path())
+        .write("Archived draft", october())
+        .expect("Failed to write draft");
+
+    assert_eq!(
+        written.archived,
+        Some(glean_dir.path().join("tech-updates-2026-10.md"))
+    );
+
+    let tool = GleanTool::new(GleanConfig {
+        dir: glean_dir.path().to_string_lossy().to_string(),
+        filter: None,
+    })
+    .expect("Failed to create glean tool");
+    let context = tool.gather_context().expect("Failed to gather context");
+    assert_eq!(context, "# tech-updates-2026-10.md\n\nArchived draft");
+}
+
+#[test]
+fn checks_archive_destination_before_writing() {
+    let out_dir = tempdir().expect("Failed to create tempdir");
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+    fs::write(glean_dir.path().join("tech-updates-2026-10.md"), "Old")
+        .expect("Failed to write file");
+
+    let err = DraftWriter::new(out_dir.path(), "tech-updates")
+        .archive_to(glean_dir.path())
+        .write("Draft", october())
+        .expect_err("Expected archive overwrite to be refused");
+
+    assert!(matches!(err, OutputError::AlreadyExists(_)));
+    assert!(!out_dir.path().join("tech-updates-2026-10.md").exists());
+}
+
+#[test]
+fn checks_destinations_without_a_draft() {
+    let out_dir = tempdir().expect("Failed to create tempdir");
+    let glean_dir = tempdir().expect("Failed to create tempdir");
+    let writer = DraftWriter::new(out_dir.path(), "tech-updates").archive_to(glean_dir.path());
+
+    writer
+        .check_destinations(october())
+        .expect("Nothing exists yet");
+
+    fs::write(glean_dir.path().join("tech-updates-2026-10.md"), "Old")
+        .expect("Failed to write file");
+    match writer.check_destinations(october()) {
+        Err(OutputError::AlreadyExists(path)) => {
+            assert!(path.starts_with(&glean_dir.path().display().to_string()))
+        }
+        other => panic!("Unexpected result: {other:?}"),
+    }
+    writer
+        .force(true)
+        .check_destinations(october())
+        .expect("Forced writes may overwrite");
+}
@@ -1,12 +1,11 @@
-#[path = "../common/mod.rs"]
-mod common;
-
-use common::with_newsagent_env;
+use crate::common::with_newsagent_env;
 use newsagent::tools::discourse::{
-    DiscourseArgs, DiscourseConfig, DiscourseInstance, DiscourseTool, DiscourseToolError,
+    DiscourseArgs, DiscourseConfig, DiscourseInstance, DiscourseReply, DiscourseTool,
+    DiscourseToolError,
 };
 use rig::tool::Tool;
-use wiremock::matchers::{header, method, path};
+use serde_json::json;
+use wiremock::matchers::{header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 // -- Config tests --
@@ -111,7 +110,9 @@ fn tool_with_instance(base_url: &str, api_key: Option<&str>, max_chars: usize) -
             instances: vec![DiscourseInstance {
                 base_url: base_url.to_string(),
                 api_key: api_key.map(|k| k.to_string()),
+                api_username: None,
             }],
+            ..DiscourseConfig::default()
         },
         max_chars,
     )
@@ -145,7 +146,10 @@ async fn fetches_topic_from_api() {
     let tool = tool_with_instance(&host, Some("test-key"), 8000);
     let url = format!("{}/t/some-slug/12345", server.uri());
     let output = tool
-        .call(DiscourseArgs { url: url.clone() })
+        .call(DiscourseArgs {
+            url: url.clone(),
+            full_thread: false,
+        })
         .await
         .expect("Discourse tool call failed");
 
@@ -176,7 +180,10 @@ async fn fetches_topic_without_api_key() {
     let tool = tool_with_instance(&host, None, 8000);
     let url = format!("{}/t/some-slug/555", server.uri());
     let output = tool
-        .call(DiscourseArgs { url: url.clone() })
+        .call(DiscourseArgs {
+            url: url.clone(),
+            full_thread: false,
+        })
         .await
         .expect("Discourse tool call failed");
 
@@ -207,7 +214,10 @@ async fn fetches_specific_post_number() {
     let tool = tool_with_instance(&host, Some("key"), 8000);
     let url = format!("{}/t/slug/99/2", server.uri());
     let output = tool
-        .call(DiscourseArgs { url })
+        .call(DiscourseArgs {
+            url,
+            full_thread: false,
+        })
         .await
         .expect("Discourse tool call failed");
 
@@ -222,6 +232,7 @@ async fn rejects_url_with_no_matching_instance() {
     let err = tool
         .call(DiscourseArgs {
             url: "https://other.example.com/t/slug/123".to_string(),
+            full_thread: false,
         })
         .await
         .expect_err("Expected NoMatchingInstance error");
@@ -241,6 +252,7 @@ async fn rejects_non_discourse_url() {
     let err = tool
         .call(DiscourseArgs {
             url: "https://discourse.example.com/categories".to_string(),
+            full_thread: false,
         })
         .await
         .expect_err("Expected NotATopicUrl error");
@@ -277,10 +289,176 @@ async fn truncates_long_content() {
     let tool = tool_with_instance(&host, Some("key"), 10);
     let url = format!("{}/t/slug/1", server.uri());
     let output = tool
-        .call(DiscourseArgs { url })
+        .call(DiscourseArgs {
+            url,
+            full_thread: false,
+        })
         .await
         .expect("Discourse tool call failed");
 
     assert!(output.text.chars().count() <= 10);
     assert!(output.truncated);
 }
+
+fn thread_post(number: u64, likes: u64) -> serde_json::Value {
+    json!({
+        "id": 100 + number,
+        "post_number": number,
+        "username": format!("user{}", number),
+        "created_at": format!("2025-06-{:02}T12:00:00Z", number),
+        "cooked": format!("<p>Post {}</p>", number),
+        "actions_summary": [{"id": 2, "count": likes}]
+    })
+}
+
+#[tokio::test]
+async fn fetches_full_thread_across_pages() {
+    let server = MockServer::start().await;
+    let host = server.uri().replace("http://", "");
+
+    // 23 posts: the topic carries the first two, the rest come from two posts.json pages.
+    let stream: Vec<u64> = (1..=23).map(|n| 100 + n).collect();
+    Mock::given(method("GET"))
+        .and(path("/t/7.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "title": "Juju 4.0.1 released",
+            "post_stream": {
+                "posts": [thread_post(1, 5), thread_post(2, 0)],
+                "stream": stream
+            }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/t/7/posts.json"))
+        .and(query_param("post_ids[]", "103"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "post_stream": {"posts": (3..=22).rev().map(|n| thread_post(n, n)).collect::<Vec<_>>()}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/t/7/posts.json"))
+        .and(query_param("post_ids[]", "123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "post_stream": {"posts": [{
+                "id": 123,
+                "post_number": 23,
+                "username": "maintainer",
+                "created_at": "2025-06-23T12:00:00Z",
+                "cooked": "<p>Correction: the snap is in <code>4.0/stable</code>.</p>",
+                "like_count": 9
+            }]}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let tool = tool_with_instance(&host, None, 8000);
+    let output = tool
+        .call(DiscourseArgs {
+            url: format!("{}/t/juju-4-0-1/7/5", server.uri()),
+            full_thread: true,
+        })
+        .await
+        .expect("Discourse tool call failed");
+
+    assert_eq!(output.title, "Juju 4.0.1 released");
+    assert_eq!(output.author, "user1");
+    assert_eq!(output.text.trim(), "Post 1");
+    assert_eq!(output.replies.len(), 22);
+    assert_eq!(
+        output.replies[0],
+        DiscourseReply {
+            post_number: 2,
+            author: "user2".to_string(),
+            date: "2025-06-02T12:00:00Z".to_string(),
+            likes: 0,
+            text: "Post 2".to_string(),
+        }
+    );
+    assert_eq!(output.replies[1].post_number, 3);
+    assert_eq!(output.replies[1].likes, 3);
+    let last = output.replies.last().unwrap();
+    assert_eq!(last.author, "maintainer");
+    assert_eq!(last.likes, 9);
+    assert!(last.text.contains("Correction"));
+    assert!(!output.truncated);
+}
+
+#[tokio::test]
+async fn full_thread_shares_max_chars_across_posts() {
+    let server = MockServer::start().await;
+    let host = server.uri().replace("http://", "");
+
+    Mock::given(method("GET"))
+        .and(path("/t/8.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "title": "Long thread",
+            "post_stream": {
+                "posts": [thread_post(1, 0), thread_post(2, 0), thread_post(3, 0)],
+                "stream": [101, 102, 103, 104]
+            }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/t/8/posts.json"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // "Post 1" and "Post 2" fit, "Post 3" is cut and post 4 is never requested.
+    let tool = tool_with_instance(&host, None, 15);
+    let output = tool
+        .call(DiscourseArgs {
+            url: format!("{}/t/long/8", server.uri()),
+            full_thread: true,
+        })
+        .await
+        .expect("Discourse tool call failed");
+
+    let texts: Vec<&str> = output.replies.iter().map(|r| r.text.as_str()).collect();
+    assert_eq!(output.text, "Post 1");
+    assert_eq!(texts, vec!["Post 2", "Pos"]);
+    assert!(output.truncated);
+}
+
+#[tokio::test]
+async fn converts_cooked_html_to_markdown() {
+    let server = MockServer::start().await;
+    let host = server.uri().replace("http://", "");
+
+    Mock::given(method("GET"))
+        .and(path("/t/42.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "title": "Charmcraft 4.1",
+            "post_stream": {"posts": [{
+                "post_number": 1,
+                "username": "alice",
+                "created_at": "2025-06-01T12:00:00Z",
+                "cooked": "<h2>Highlights</h2><ul><li>Fixed in <a href=\"https://github.com/canonical/charmcraft/pull/42\">#42</a></li><li>See <a href=\"/t/migration/7\">migration</a></li></ul><pre><code class=\"lang-bash\">charmcraft pack</code></pre><p>Q&amp;A &mdash; thanks!</p>"
+            }]}
+        })))
+        .mount(&server)
+        .await;
+
+    let tool = tool_with_instance(&host, None, 8000);
+    let output = tool
+        .call(DiscourseArgs {
+            url: format!("{}/t/charmcraft-4-1/42", server.uri()),
+            full_thread: false,
+        })
+        .await
+        .expect("Discourse tool call failed");
+
+    assert_eq!(
+        output.text,
+        format!(
+            "## Highlights\n\n- Fixed in [#42](https://github.com/canonical/charmcraft/pull/42)\n- See [migration]({}/t/migration/7)\n\n```bash\ncharmcraft pack\n```\n\nQ&A — thanks!",
+            server.uri()
+        )
+    );
+}
@@ -0,0 +1,241 @@
+use crate::common::with_newsagent_env;
+use chrono::{Duration, Utc};
+use newsagent::tools::discourse::{
+    DiscourseConfig, DiscourseInstance, DiscourseSource, DiscourseSourceKind,
+};
+use newsagent::tools::discourse_recent::{DiscourseRecentArgs, DiscourseRecentTool};
+use rig::tool::Tool;
+use serde_json::json;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// -- Config tests --
+
+#[test]
+fn config_parses_recent_sources() {
+    let _guard = with_newsagent_env(vec![
+        (
+            "NEWSAGENT_DISCOURSE_RECENT",
+            "discourse.ubuntu.com=c/announcements/41, tag/release",
+        ),
+        ("NEWSAGENT_DISCOURSE_RECENT_DAYS", "7"),
+    ]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<DiscourseConfig>()
+        .expect("Failed to parse DiscourseConfig from env");
+
+    assert_eq!(
+        config.recent,
+        vec![
+            DiscourseSource {
+                host: Some("discourse.ubuntu.com".to_string()),
+                kind: DiscourseSourceKind::Category {
+                    slug: "announcements".to_string(),
+                    id: 41,
+                },
+            },
+            DiscourseSource {
+                host: None,
+                kind: DiscourseSourceKind::Tag("release".to_string()),
+            },
+        ]
+    );
+    assert_eq!(config.effective_recent_days(), 7);
+}
+
+#[test]
+fn config_rejects_malformed_sources() {
+    let _guard = with_newsagent_env(vec![("NEWSAGENT_DISCOURSE_RECENT", "c/announcements")]);
+
+    let err = envy::prefixed("NEWSAGENT_")
+        .from_env::<DiscourseConfig>()
+        .expect_err("Expected malformed source to fail");
+
+    assert!(err
+        .to_string()
+        .contains("invalid Discourse source `c/announcements`"));
+}
+
+// -- Tool tests --
+
+fn instance(server: &MockServer) -> DiscourseInstance {
+    DiscourseInstance {
+        base_url: server.uri(),
+        api_key: None,
+        api_username: None,
+    }
+}
+
+fn source(host: Option<&str>, kind: DiscourseSourceKind) -> DiscourseSource {
+    DiscourseSource {
+        host: host.map(str::to_string),
+        kind,
+    }
+}
+
+fn topic(id: u64, title: &str, days_ago: i64, user_id: i64) -> serde_json::Value {
+    json!({
+        "id": id,
+        "title": title,
+        "slug": title.to_lowercase().replace(' ', "-"),
+        "created_at": (Utc::now() - Duration::days(days_ago)).to_rfc3339(),
+        "reply_count": id % 7,
+        "views": id * 10,
+        "posters": [
+            {"user_id": 99, "description": "Frequent Poster"},
+            {"user_id": user_id, "description": "Original Poster, Most Recent Poster"}
+        ]
+    })
+}
+
+#[test]
+fn new_returns_none_without_sources() {
+    let config = DiscourseConfig {
+        instances: vec![DiscourseInstance {
+            base_url: "discourse.ubuntu.com".to_string(),
+            api_key: None,
+            api_username: None,
+        }],
+        recent: vec![source(
+            Some("discourse.charmhub.io"),
+            DiscourseSourceKind::Tag("release".to_string()),
+        )],
+        ..DiscourseConfig::default()
+    };
+
+    assert!(DiscourseRecentTool::new(config)
+        .expect("Failed to create DiscourseRecentTool")
+        .is_none());
+    assert!(DiscourseRecentTool::new(DiscourseConfig::default())
+        .expect("Failed to create DiscourseRecentTool")
+        .is_none());
+}
+
+#[tokio::test]
+async fn lists_recent_topics_from_categories_and_tags() {
+    let server = MockServer::start().await;
+    let users = json!([
+        {"id": 1, "username": "alice"},
+        {"id": 2, "username": "bob"},
+        {"id": 99, "username": "moderator"}
+    ]);
+
+    let mut pinned = topic(1, "About the Announcements category", 400, 99);
+    pinned["pinned"] = json!(true);
+    Mock::given(method("GET"))
+        .and(path("/c/announcements/41.json"))
+        .and(query_param("page", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "users": users,
+            "topic_list": {
+                "topics": [pinned, topic(30, "Juju 4.0.1 released", 1, 1)],
+                "more_topics_url": "/c/announcements/41?page=1"
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/c/announcements/41.json"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "users": users,
+            "topic_list": {
+                "topics": [topic(20, "Pebble 1.27 released", 5, 2), topic(10, "Old news", 30, 2)],
+                "more_topics_url": "/c/announcements/41?page=2"
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tag/release.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "users": users,
+            "topic_list": {
+                "topics": [topic(30, "Juju 4.0.1 released", 1, 1), topic(25, "Snapcraft 8.14", 3, 2)]
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let tool = DiscourseRecentTool::new(DiscourseConfig {
+        instances: vec![instance(&server)],
+        recent: vec![
+            source(
+                None,
+                DiscourseSourceKind::Category {
+                    slug: "announcements".to_string(),
+                    id: 41,
+                },
+            ),
+            source(None, DiscourseSourceKind::Tag("release".to_string())),
+        ],
+        recent_days: Some(14),
+    })
+    .expect("Failed to create DiscourseRecentTool")
+    .expect("Expected a DiscourseRecentTool");
+
+    let output = tool
+        .call(DiscourseRecentArgs {})
+        .await
+        .expect("discourse_recent call failed");
+
+    let host = server.uri().replace("http://", "");
+    let titles: Vec<&str> = output.topics.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        vec![
+            "Juju 4.0.1 released",
+            "Snapcraft 8.14",
+            "Pebble 1.27 released"
+        ]
+    );
+
+    let juju = &output.topics[0];
+    assert_eq!(
+        juju.url,
+        format!("{}/t/juju-4.0.1-released/30", server.uri())
+    );
+    assert_eq!(juju.author, "alice");
+    assert_eq!(juju.reply_count, 2);
+    assert_eq!(juju.views, 300);
+    assert_eq!(
+        juju.sources,
+        vec![
+            format!("{} c/announcements/41", host),
+            format!("{} tag/release", host)
+        ]
+    );
+    assert_eq!(output.topics[2].author, "bob");
+}
+
+#[tokio::test]
+async fn skips_sources_that_fail() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/tag/missing.json"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let tool = DiscourseRecentTool::new(DiscourseConfig {
+        instances: vec![instance(&server)],
+        recent: vec![source(
+            None,
+            DiscourseSourceKind::Tag("missing".to_string()),
+        )],
+        ..DiscourseConfig::default()
+    })
+    .expect("Failed to create DiscourseRecentTool")
+    .expect("Expected a DiscourseRecentTool");
+
+    let output = tool
+        .call(DiscourseRecentArgs {})
+        .await
+        .expect("discourse_recent call failed");
+
+    assert!(output.topics.is_empty());
+}
@@ -0,0 +1,150 @@
+use crate::common::with_newsagent_env;
+use newsagent::tools::github::{
+    GithubConfig, GithubReleaseArgs, GithubReleaseTool, GithubReleaseToolError,
+};
+use rig::tool::Tool;
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn tool_for(server: &MockServer, token: Option<&str>, max_chars: usize) -> GithubReleaseTool {
+    GithubReleaseTool::new(
+        GithubConfig {
+            token: token.map(str::to_string),
+            api_url: server.uri(),
+        },
+        max_chars,
+    )
+    .expect("Failed to create GitHub tool")
+}
+
+// -- Config tests --
+
+#[test]
+fn config_parses_token_and_defaults_api_url() {
+    let _guard = with_newsagent_env(vec![("NEWSAGENT_GITHUB_TOKEN", "ghp_secret")]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<GithubConfig>()
+        .expect("Failed to parse GithubConfig from env");
+
+    assert_eq!(config.token.as_deref(), Some("ghp_secret"));
+    assert_eq!(config.api_url, "https://api.github.com");
+}
+
+#[test]
+fn recognises_release_urls() {
+    assert!(GithubReleaseTool::handles_url(
+        "https://github.com/canonical/pebble/releases/tag/v1.27.0"
+    ));
+    assert!(!GithubReleaseTool::handles_url(
+        "https://github.com/canonical/pebble/pull/567"
+    ));
+    assert!(!GithubReleaseTool::handles_url(
+        "https://discourse.charmhub.io/t/foo/1"
+    ));
+}
+
+// -- Tool call tests --
+
+#[tokio::test]
+async fn fetches_release_with_linked_pull_requests() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/canonical/pebble/releases/tags/v1.27.0"))
+        .and(header("Authorization", "Bearer ghp_secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "tag_name": "v1.27.0",
+            "name": "v1.27.0",
+            "published_at": "2025-06-02T10:00:00Z",
+            "author": { "login": "benhoyt" },
+            "html_url": "https://github.com/canonical/pebble/releases/tag/v1.27.0",
+            "body": "## What's Changed\n* feat: syslog target by @a in https://github.com/canonical/pebble/pull/567\n* fix: layer race (#571)\n"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let output = tool_for(&server, Some("ghp_secret"), 8000)
+        .call(GithubReleaseArgs {
+            url: "https://github.com/canonical/pebble/releases/tag/v1.27.0".to_string(),
+        })
+        .await
+        .expect("GitHub tool call failed");
+
+    assert_eq!(output.repository, "canonical/pebble");
+    assert_eq!(output.tag, "v1.27.0");
+    assert_eq!(output.name, "v1.27.0");
+    assert_eq!(output.published_at.as_deref(), Some("2025-06-02T10:00:00Z"));
+    assert_eq!(output.author, "benhoyt");
+    assert!(output.body.contains("syslog target"));
+    assert_eq!(output.pull_requests, vec![567, 571]);
+    assert!(!output.truncated);
+}
+
+#[tokio::test]
+async fn falls_back_to_tag_when_release_is_unnamed_and_truncates_body() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/canonical/rockcraft/releases/tags/1.16.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "tag_name": "1.16.0",
+            "name": "",
+            "published_at": null,
+            "author": null,
+            "body": "A long body of release notes"
+        })))
+        .mount(&server)
+        .await;
+
+    let output = tool_for(&server, None, 6)
+        .call(GithubReleaseArgs {
+            url: "https://github.com/canonical/rockcraft/releases/tag/1.16.0".to_string(),
+        })
+        .await
+        .expect("GitHub tool call failed");
+
+    assert_eq!(output.name, "1.16.0");
+    assert_eq!(output.body, "A long");
+    assert!(output.truncated);
+    assert_eq!(
+        output.source_url,
+        "https://github.com/canonical/rockcraft/releases/tag/1.16.0"
+    );
+}
+
+#[tokio::test]
+async fn rejects_non_release_url() {
+    let server = MockServer::start().await;
+
+    let err = tool_for(&server, None, 8000)
+        .call(GithubReleaseArgs {
+            url: "https://github.com/canonical/pebble/pull/567".to_string(),
+        })
+        .await
+        .expect_err("Expected non-release URL to fail");
+
+    assert!(matches!(err, GithubReleaseToolError::NotAReleaseUrl(_)));
+}
+
+#[tokio::test]
+async fn surfaces_api_errors() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/canonical/pebble/releases/tags/v0.0.0"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let err = tool_for(&server, None, 8000)
+        .call(GithubReleaseArgs {
+            url: "https://github.com/canonical/pebble/releases/tag/v0.0.0".to_string(),
+        })
+        .await
+        .expect_err("Expected missing release to fail");
+
+    assert!(matches!(err, GithubReleaseToolError::Other(_)));
+}
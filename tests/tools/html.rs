@@ -0,0 +1,127 @@
+use newsagent::tools::html::{extract_links, html_to_markdown, Link};
+use url::Url;
+
+#[test]
+fn converts_headings_paragraphs_and_emphasis() {
+    let html = "<h2>Juju 4.0.1</h2><p>This is a <strong>patch</strong> release with <em>important</em>\n   fixes.</p><h3>Changes</h3>";
+
+    assert_eq!(
+        html_to_markdown(html, None),
+        "## Juju 4.0.1\n\nThis is a **patch** release with *important* fixes.\n\n### Changes"
+    );
+}
+
+#[test]
+fn keeps_links_resolved_against_base() {
+    let base = Url::parse("https://discourse.ubuntu.com/t/juju-4-0-1/123").unwrap();
+    let html = r##"<p>Fixed in <a href="https://github.com/juju/juju/pull/123">#123</a>, see <a href="/t/upgrade-notes/99">the notes</a>, <a href="#heading">this section</a> and <a href="https://juju.is"></a>.</p>"##;
+
+    assert_eq!(
+        html_to_markdown(html, Some(&base)),
+        "Fixed in [#123](https://github.com/juju/juju/pull/123), see [the notes](https://discourse.ubuntu.com/t/upgrade-notes/99), this section and <https://juju.is/>."
+    );
+}
+
+#[test]
+fn keeps_code_blocks_and_inline_code() {
+    let html = "<p>Run <code>snap refresh juju</code>:</p><pre><code class=\"lang-bash\">sudo snap refresh juju \\\n  --channel 4.0/stable\n</code></pre>";
+
+    assert_eq!(
+        html_to_markdown(html, None),
+        "Run `snap refresh juju`:\n\n```bash\nsudo snap refresh juju \\\n  --channel 4.0/stable\n```"
+    );
+}
+
+#[test]
+fn keeps_blank_lines_and_trailing_spaces_inside_code_blocks() {
+    let html = "<p>Before</p><pre><code>first  \n\n\n\nlast\n</code></pre><p>After</p>";
+
+    assert_eq!(
+        html_to_markdown(html, None),
+        "Before\n\n```\nfirst  \n\n\n\nlast\n```\n\nAfter"
+    );
+}
+
+#[test]
+fn keeps_tilde_fenced_code_with_backtick_fences_intact() {
+    let html =
+        "<pre><code>Example:  \n\n\n```sh\nmake  \n</code></pre><p>After</p>\n\n\n<p>End</p>";
+
+    assert_eq!(
+        html_to_markdown(html, None),
+        "~~~\nExample:  \n\n\n```sh\nmake  \n~~~\n\nAfter\n\nEnd"
+    );
+}
+
+#[test]
+fn keeps_list_structure() {
+    let html = "<ul><li>First</li><li>Second<ul><li>Nested <a href=\"https://x.test/a\">link</a></li></ul></li></ul><ol start=\"3\"><li><p>Third</p></li><li>Fourth</li></ol>";
+
+    assert_eq!(
+        html_to_markdown(html, None),
+        "- First\n- Second\n  - Nested [link](https://x.test/a)\n\n3. Third\n4. Fourth"
+    );
+}
+
+#[test]
+fn leaves_out_the_header_separator_for_tables_without_headers() {
+    let html = "<table><tr><td>3.6</td><td>LTS</td></tr><tr><td>4.0</td></tr></table>";
+
+    assert_eq!(html_to_markdown(html, None), "| 3.6 | LTS |\n| 4.0 |  |");
+}
+
+#[test]
+fn decodes_all_html_entities() {
+    let html = "<p>Tom &amp; Jerry &mdash; &ldquo;caf&eacute;&rdquo; &hellip; it&#x27;s &lt;ok&gt;&nbsp;now</p>";
+
+    assert_eq!(
+        html_to_markdown(html, None),
+        "Tom & Jerry — “café” … it's <ok> now"
+    );
+}
+
+#[test]
+fn converts_quotes_tables_and_images() {
+    let html = "<blockquote><p>Quoted</p><p>Twice</p></blockquote><table><thead><tr><th>Series</th><th>Status</th></tr></thead><tbody><tr><td>3.6</td><td>LTS</td></tr></tbody></table><p><img src=\"/img/logo.png\" alt=\"Logo\"></p>";
+    let base = Url::parse("https://juju.is/docs").unwrap();
+
+    assert_eq!(
+        html_to_markdown(html, Some(&base)),
+        "> Quoted\n>\n> Twice\n\n| Series | Status |\n| --- | --- |\n| 3.6 | LTS |\n\n![Logo](https://juju.is/img/logo.png)"
+    );
+}
+
+#[test]
+fn drops_scripts_styles_and_empty_markup() {
+    let html = "<html><head><title>T</title><style>p{}</style></head><body><script>alert(1)</script><div><p></p><p>Body<br>next line</p></div></body></html>";
+
+    assert_eq!(html_to_markdown(html, None), "Body\nnext line");
+}
+
+fn link(text: &str, url: &str) -> Link {
+    Link {
+        text: text.to_string(),
+        url: url.to_string(),
+    }
+}
+
+#[test]
+fn extracts_deduplicated_links_with_anchor_text() {
+    let base = Url::parse("https://github.com/canonical/pebble/releases/tag/v1.27.0").unwrap();
+    let html = r##"<p><a href="/canonical/pebble/pull/612"><img src="x.png"></a>
+        Identities landed in <a href="/canonical/pebble/pull/612">#612</a> and
+        <a href="https://documentation.ubuntu.com/pebble/">the   docs</a>.
+        <a href="#top">Top</a> <a href="mailto:team@example.com">Mail</a>
+        <a href="https://documentation.ubuntu.com/pebble/">Docs again</a>
+        <a href="https://ubuntu.com/" title="Ubuntu home"></a></p>
+        <script><a href="https://tracker.test/">hidden</a></script>"##;
+
+    assert_eq!(
+        extract_links(html, Some(&base)),
+        vec![
+            link("#612", "https://github.com/canonical/pebble/pull/612"),
+            link("the docs", "https://documentation.ubuntu.com/pebble/"),
+            link("Ubuntu home", "https://ubuntu.com/"),
+        ]
+    );
+}
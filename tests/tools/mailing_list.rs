@@ -1,14 +1,14 @@
-#[path = "../common/mod.rs"]
-mod common;
-
+use crate::common::with_newsagent_env;
 use chrono::Datelike;
-use common::with_newsagent_env;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use newsagent::tools::mailing_list::{MailingListArgs, MailingListConfig, MailingListTool};
+use newsagent::tools::mailing_list::{
+    ArchiveFormat, MailingListArgs, MailingListConfig, MailingListTool,
+};
 use rig::tool::Tool;
+use std::collections::HashMap;
 use std::io::Write;
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 // -- Config tests --
@@ -185,6 +185,7 @@ fn tool_with_server(server_uri: &str, lists: Vec<&str>) -> MailingListTool {
         MailingListConfig {
             lists: lists.into_iter().map(|s| s.to_string()).collect(),
             base_url: Some(server_uri.to_string()),
+            ..MailingListConfig::default()
         },
         8000,
     )
@@ -226,7 +227,7 @@ async fn fetches_and_parses_mbox() {
 
     let tool = tool_with_server(&server.uri(), vec!["test-list"]);
     let output = tool
-        .call(MailingListArgs {})
+        .call(MailingListArgs::default())
         .await
         .expect("Tool call failed");
 
@@ -278,7 +279,7 @@ async fn filters_messages_to_last_30_days() {
 
     let tool = tool_with_server(&server.uri(), vec!["test-list"]);
     let output = tool
-        .call(MailingListArgs {})
+        .call(MailingListArgs::default())
         .await
         .expect("Tool call failed");
 
@@ -324,7 +325,7 @@ async fn threads_messages_by_reply() {
 
     let tool = tool_with_server(&server.uri(), vec!["test-list"]);
     let output = tool
-        .call(MailingListArgs {})
+        .call(MailingListArgs::default())
         .await
         .expect("Tool call failed");
 
@@ -334,6 +335,100 @@ async fn threads_messages_by_reply() {
     assert!(output.threads[0].authors.contains(&"Bob".to_string()));
 }
 
+async fn mount_reply_thread(server: &MockServer) {
+    let mbox = [
+        make_mbox_message(
+            "r1@example.com",
+            "Alice",
+            "alice@example.com",
+            "Dropping i386",
+            "Mon, 01 Dec 2025 10:00:00 +0000",
+            "We plan to drop i386 next cycle.\n\n-- \nAlice",
+            None,
+        ),
+        make_mbox_message(
+            "r3@example.com",
+            "Carol",
+            "carol@example.com",
+            "Re: Dropping i386",
+            "Wed, 03 Dec 2025 10:00:00 +0000",
+            "Steam still needs it.\n\nOn Tue, 2 Dec 2025, Bob wrote:\n> Fine by me.",
+            Some("r2@example.com"),
+        ),
+        make_mbox_message(
+            "r2@example.com",
+            "Bob",
+            "bob@example.com",
+            "Re: Dropping i386",
+            "Tue, 02 Dec 2025 10:00:00 +0000",
+            "On Mon, 1 Dec 2025, Alice wrote:\n> We plan to drop i386 next cycle.\n\nFine by me.\n_______________________________________________\nubuntu-devel mailing list",
+            Some("r1@example.com"),
+        ),
+    ]
+    .concat();
+    Mock::given(method("GET"))
+        .and(path("/ubuntu-devel/2025-December.txt.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(mbox.as_bytes())))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn outlines_replies_without_quotes_or_signatures() {
+    let server = MockServer::start().await;
+    mount_reply_thread(&server).await;
+    let tool = tool_with_server(&server.uri(), vec!["ubuntu-devel"]);
+
+    let output = tool
+        .call(december(MailingListArgs::default()))
+        .await
+        .expect("Tool call failed");
+
+    let thread = &output.threads[0];
+    assert_eq!(thread.summary, "We plan to drop i386 next cycle.");
+    let outline: Vec<(&str, &str, &str)> = thread
+        .replies
+        .iter()
+        .map(|r| (r.author.as_str(), r.date.as_str(), r.excerpt.as_str()))
+        .collect();
+    assert_eq!(
+        outline,
+        [
+            ("Bob", "2025-12-02T10:00:00", "Fine by me."),
+            ("Carol", "2025-12-03T10:00:00", "Steam still needs it."),
+        ]
+    );
+    assert_eq!(thread.omitted_replies, 0);
+    assert!(!thread.truncated);
+}
+
+#[tokio::test]
+async fn shares_the_character_budget_between_summary_and_replies() {
+    let server = MockServer::start().await;
+    mount_reply_thread(&server).await;
+    let tool = MailingListTool::new(
+        MailingListConfig {
+            lists: vec!["ubuntu-devel".to_string()],
+            base_url: Some(server.uri()),
+            ..MailingListConfig::default()
+        },
+        22,
+    )
+    .expect("Failed to create tool");
+
+    let output = tool
+        .call(december(MailingListArgs::default()))
+        .await
+        .expect("Tool call failed");
+
+    let thread = &output.threads[0];
+    assert_eq!(thread.summary, "We plan to ");
+    assert_eq!(thread.replies.len(), 1);
+    assert_eq!(thread.replies[0].excerpt, "Fine by me.");
+    assert_eq!(thread.omitted_replies, 1);
+    assert!(thread.truncated);
+}
+
 #[tokio::test]
 async fn deduplicates_across_lists() {
     let server = MockServer::start().await;
@@ -368,7 +463,7 @@ async fn deduplicates_across_lists() {
 
     let tool = tool_with_server(&server.uri(), vec!["list-a", "list-b"]);
     let output = tool
-        .call(MailingListArgs {})
+        .call(MailingListArgs::default())
         .await
         .expect("Tool call failed");
 
@@ -406,13 +501,14 @@ async fn truncates_message_body() {
         MailingListConfig {
             lists: vec!["test-list".to_string()],
             base_url: Some(server.uri()),
+            ..MailingListConfig::default()
         },
         50,
     )
     .expect("Failed to create tool");
 
     let output = tool
-        .call(MailingListArgs {})
+        .call(MailingListArgs::default())
         .await
         .expect("Tool call failed");
 
@@ -442,7 +538,7 @@ async fn handles_missing_month_gracefully() {
 
     let tool = tool_with_server(&server.uri(), vec!["test-list"]);
     let output = tool
-        .call(MailingListArgs {})
+        .call(MailingListArgs::default())
         .await
         .expect("Tool call should succeed even with missing archives");
 
@@ -493,7 +589,7 @@ async fn sorts_threads_by_recent_activity() {
 
     let tool = tool_with_server(&server.uri(), vec!["test-list"]);
     let output = tool
-        .call(MailingListArgs {})
+        .call(MailingListArgs::default())
         .await
         .expect("Tool call failed");
 
@@ -501,3 +597,470 @@ async fn sorts_threads_by_recent_activity() {
     assert_eq!(output.threads[0].subject, "Newer Topic");
     assert_eq!(output.threads[1].subject, "Older Topic");
 }
+
+#[test]
+fn config_parses_lookback_days() {
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_MAILING_LISTS", "ubuntu-release"),
+        ("NEWSAGENT_MAILING_LIST_LOOKBACK_DAYS", "90"),
+    ]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<MailingListConfig>()
+        .expect("Failed to parse MailingListConfig from env");
+
+    assert_eq!(config.effective_lookback_days(), 90);
+    assert_eq!(MailingListConfig::default().effective_lookback_days(), 30);
+}
+
+#[tokio::test]
+async fn fetches_every_month_in_explicit_range() {
+    let server = MockServer::start().await;
+
+    let archives = [
+        (
+            "2025-November",
+            vec![
+                (
+                    "before@example.com",
+                    "Too early",
+                    "Fri, 14 Nov 2025 23:59:59 +0000",
+                ),
+                (
+                    "start@example.com",
+                    "First day",
+                    "Sat, 15 Nov 2025 00:00:00 +0000",
+                ),
+            ],
+        ),
+        (
+            "2025-December",
+            vec![(
+                "middle@example.com",
+                "Middle",
+                "Mon, 15 Dec 2025 10:00:00 +0000",
+            )],
+        ),
+        (
+            "2026-January",
+            vec![
+                (
+                    "end@example.com",
+                    "Last day",
+                    "Sat, 10 Jan 2026 23:59:00 +0000",
+                ),
+                (
+                    "after@example.com",
+                    "Too late",
+                    "Sun, 11 Jan 2026 00:00:01 +0000",
+                ),
+            ],
+        ),
+    ];
+    for (month, messages) in archives {
+        let mbox: String = messages
+            .into_iter()
+            .map(|(id, subject, date)| {
+                make_mbox_message(
+                    id,
+                    "Alice",
+                    "alice@example.com",
+                    subject,
+                    date,
+                    "Body",
+                    None,
+                )
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path(format!("/test-list/{}.txt.gz", month)))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(mbox.as_bytes())))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let tool = tool_with_server(&server.uri(), vec!["test-list"]);
+    let output = tool
+        .call(MailingListArgs {
+            since: Some("2025-11-15".to_string()),
+            until: Some("2026-01-10".to_string()),
+            ..MailingListArgs::default()
+        })
+        .await
+        .expect("Tool call failed");
+
+    assert_eq!(output.since, "2025-11-15");
+    assert_eq!(output.until, "2026-01-10");
+    let subjects: Vec<_> = output.threads.iter().map(|t| t.subject.as_str()).collect();
+    assert_eq!(subjects, ["Last day", "Middle", "First day"]);
+}
+
+#[tokio::test]
+async fn rejects_malformed_dates() {
+    let tool = tool_with_server("http://127.0.0.1:9", vec!["test-list"]);
+    let err = tool
+        .call(MailingListArgs {
+            since: Some("last month".to_string()),
+            until: None,
+            ..MailingListArgs::default()
+        })
+        .await
+        .expect_err("Expected invalid date");
+
+    assert_eq!(
+        err.to_string(),
+        "invalid `since` date `last month`, expected YYYY-MM-DD"
+    );
+}
+
+async fn mount_december_lists(server: &MockServer) {
+    let devel = [
+        make_mbox_message(
+            "a1@example.com",
+            "Alice",
+            "alice@example.com",
+            "Security update for openssl",
+            "Sat, 20 Dec 2025 10:00:00 +0000",
+            "A fix is in -proposed.",
+            None,
+        ),
+        make_mbox_message(
+            "a2@example.com",
+            "Bob",
+            "bob@example.com",
+            "Re: Security update for openssl",
+            "Sun, 21 Dec 2025 10:00:00 +0000",
+            "Thanks, verified.",
+            Some("a1@example.com"),
+        ),
+        make_mbox_message(
+            "a3@example.com",
+            "Carol",
+            "carol@example.com",
+            "Re: Security update for openssl",
+            "Mon, 22 Dec 2025 10:00:00 +0000",
+            "Released to -updates.",
+            Some("a2@example.com"),
+        ),
+        make_mbox_message(
+            "b1@example.com",
+            "Dave",
+            "dave@example.com",
+            "Archive opening",
+            "Sun, 28 Dec 2025 10:00:00 +0000",
+            "Snapcraft 9 lands next week.",
+            None,
+        ),
+    ]
+    .concat();
+    let release = [
+        make_mbox_message(
+            "c1@example.com",
+            "Erin",
+            "erin@example.com",
+            "Release schedule",
+            "Fri, 05 Dec 2025 10:00:00 +0000",
+            "Dates for the next release.",
+            None,
+        ),
+        make_mbox_message(
+            "c2@example.com",
+            "Erin",
+            "erin@example.com",
+            "Re: Release schedule",
+            "Sat, 06 Dec 2025 10:00:00 +0000",
+            "Corrected dates.",
+            Some("c1@example.com"),
+        ),
+    ]
+    .concat();
+    for (list, mbox) in [("ubuntu-devel", devel), ("ubuntu-release", release)] {
+        Mock::given(method("GET"))
+            .and(path(format!("/{}/2025-December.txt.gz", list)))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(mbox.as_bytes())))
+            .mount(server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/technical-board/2025-December.txt.gz"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(0)
+        .mount(server)
+        .await;
+}
+
+fn december(args: MailingListArgs) -> MailingListArgs {
+    MailingListArgs {
+        since: Some("2025-12-01".to_string()),
+        until: Some("2025-12-31".to_string()),
+        ..args
+    }
+}
+
+fn subjects(output: &newsagent::tools::mailing_list::MailingListOutput) -> Vec<&str> {
+    output.threads.iter().map(|t| t.subject.as_str()).collect()
+}
+
+#[tokio::test]
+async fn ranks_threads_by_activity_and_caps_the_result() {
+    let server = MockServer::start().await;
+    mount_december_lists(&server).await;
+    let tool = tool_with_server(
+        &server.uri(),
+        vec!["ubuntu-devel", "ubuntu-release", "technical-board"],
+    );
+
+    let output = tool
+        .call(december(MailingListArgs {
+            lists: vec!["ubuntu-*".to_string()],
+            limit: Some(2),
+            ..MailingListArgs::default()
+        }))
+        .await
+        .expect("Tool call failed");
+
+    assert_eq!(
+        subjects(&output),
+        ["Security update for openssl", "Archive opening"]
+    );
+    assert_eq!(output.omitted, 1);
+    assert!(output.threads[0].score > output.threads[1].score);
+}
+
+#[tokio::test]
+async fn filters_threads_by_keyword_author_and_size() {
+    let server = MockServer::start().await;
+    mount_december_lists(&server).await;
+    let tool = tool_with_server(
+        &server.uri(),
+        vec!["ubuntu-devel", "ubuntu-release", "technical-board"],
+    );
+    let call = |args: MailingListArgs| {
+        let tool = tool.clone();
+        async move {
+            tool.call(december(MailingListArgs {
+                exclude_lists: vec!["technical-*".to_string()],
+                ..args
+            }))
+            .await
+            .expect("Tool call failed")
+        }
+    };
+
+    let output = call(MailingListArgs {
+        keywords: vec!["SNAPCRAFT".to_string()],
+        ..MailingListArgs::default()
+    })
+    .await;
+    assert_eq!(subjects(&output), ["Archive opening"]);
+
+    let output = call(MailingListArgs {
+        authors: vec!["erin".to_string()],
+        ..MailingListArgs::default()
+    })
+    .await;
+    assert_eq!(subjects(&output), ["Release schedule"]);
+
+    let output = call(MailingListArgs {
+        min_messages: Some(2),
+        ..MailingListArgs::default()
+    })
+    .await;
+    assert_eq!(
+        subjects(&output),
+        ["Security update for openssl", "Release schedule"]
+    );
+    assert_eq!(output.omitted, 0);
+}
+
+#[test]
+fn config_parses_max_threads() {
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_MAILING_LISTS", "ubuntu-release"),
+        ("NEWSAGENT_MAILING_LIST_MAX_THREADS", "5"),
+    ]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<MailingListConfig>()
+        .expect("Failed to parse MailingListConfig from env");
+
+    assert_eq!(config.effective_max_threads(), 5);
+    assert_eq!(MailingListConfig::default().effective_max_threads(), 20);
+}
+
+#[test]
+fn config_parses_archive_format() {
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_MAILING_LISTS", "devel@lists.example.org"),
+        ("NEWSAGENT_MAILING_LIST_ARCHIVE_FORMAT", "hyperkitty"),
+    ]);
+
+    let config = envy::prefixed("NEWSAGENT_")
+        .from_env::<MailingListConfig>()
+        .expect("Failed to parse MailingListConfig from env");
+
+    assert_eq!(config.archive_format, Some(ArchiveFormat::Hyperkitty));
+    assert_eq!(
+        MailingListConfig::default().archive_for("ubuntu-devel").1,
+        ArchiveFormat::Pipermail
+    );
+}
+
+#[test]
+fn config_rejects_unknown_archive_format() {
+    let _guard = with_newsagent_env(vec![
+        ("NEWSAGENT_MAILING_LISTS", "ubuntu-devel"),
+        ("NEWSAGENT_MAILING_LIST_ARCHIVE_FORMAT", "mbox"),
+    ]);
+
+    let err = envy::prefixed("NEWSAGENT_")
+        .from_env::<MailingListConfig>()
+        .expect_err("Expected an unknown format");
+
+    assert!(err.to_string().contains("unknown archive format `mbox`"));
+}
+
+#[tokio::test]
+async fn reads_each_list_from_its_own_archive_format() {
+    let pipermail = MockServer::start().await;
+    let hyperkitty = MockServer::start().await;
+    let public_inbox = MockServer::start().await;
+
+    let message = |id: &str, subject: &str| {
+        make_mbox_message(
+            id,
+            "Alice",
+            "alice@example.com",
+            subject,
+            "Mon, 15 Dec 2025 10:00:00 +0000",
+            "Body",
+            None,
+        )
+    };
+    Mock::given(method("GET"))
+        .and(path("/ubuntu-devel/2025-December.txt.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(
+            message("p1@example.com", "From Pipermail").as_bytes(),
+        )))
+        .expect(1)
+        .mount(&pipermail)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/archives/list/devel@lists.example.org/export/devel@lists.example.org-2025-12-01-2025-12-31.mbox.gz",
+        ))
+        .and(query_param("start", "2025-12-01"))
+        .and(query_param("end", "2026-01-01"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(
+            message("h1@example.com", "From Hyperkitty").as_bytes(),
+        )))
+        .expect(1)
+        .mount(&hyperkitty)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/git/"))
+        .and(query_param("q", "d:20251201..20260101"))
+        .and(query_param("x", "m"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(
+            message("i1@example.com", "From public-inbox").as_bytes(),
+        )))
+        .expect(1)
+        .mount(&public_inbox)
+        .await;
+
+    let tool = MailingListTool::new(
+        MailingListConfig {
+            lists: vec![
+                "ubuntu-devel".to_string(),
+                "devel@lists.example.org".to_string(),
+                "git".to_string(),
+            ],
+            base_url: Some(pipermail.uri()),
+            list_base_urls: HashMap::from([
+                (
+                    "devel@lists.example.org".to_string(),
+                    format!("{}/archives/", hyperkitty.uri()),
+                ),
+                ("git".to_string(), public_inbox.uri()),
+            ]),
+            list_archive_formats: HashMap::from([
+                (
+                    "devel@lists.example.org".to_string(),
+                    ArchiveFormat::Hyperkitty,
+                ),
+                ("git".to_string(), ArchiveFormat::PublicInbox),
+            ]),
+            ..MailingListConfig::default()
+        },
+        8000,
+    )
+    .expect("Failed to create tool");
+
+    assert_eq!(
+        tool.list_labels(),
+        [
+            "ubuntu-devel",
+            "devel@lists.example.org (hyperkitty)",
+            "git (public-inbox)"
+        ]
+    );
+
+    let output = tool
+        .call(december(MailingListArgs::default()))
+        .await
+        .expect("Tool call failed");
+
+    let mut found = subjects(&output);
+    found.sort();
+    assert_eq!(
+        found,
+        ["From Hyperkitty", "From Pipermail", "From public-inbox"]
+    );
+}
+
+#[tokio::test]
+async fn keeps_body_from_lines_and_filters_by_utc_date() {
+    let server = MockServer::start().await;
+    let mbox = [
+        make_mbox_message(
+            "late@example.com",
+            "Alice",
+            "alice@example.com",
+            "Late on the 30th",
+            "Sun, 30 Nov 2025 23:00:00 -0200",
+            "Minutes:\n\nFrom the release team, nothing new.\nFrom Dec 1 10:00 2025 the archive is frozen.",
+            None,
+        ),
+        make_mbox_message(
+            "early@example.com",
+            "Bob",
+            "bob@example.com",
+            "Early on the 1st",
+            "Mon, 01 Dec 2025 00:30:00 +0200",
+            "Still November in UTC.",
+            None,
+        ),
+    ]
+    .concat();
+    Mock::given(method("GET"))
+        .and(path("/ubuntu-devel/2025-December.txt.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(gzip_bytes(mbox.as_bytes())))
+        .mount(&server)
+        .await;
+    let tool = tool_with_server(&server.uri(), vec!["ubuntu-devel"]);
+
+    let output = tool
+        .call(december(MailingListArgs::default()))
+        .await
+        .expect("Tool call failed");
+
+    assert_eq!(subjects(&output), ["Late on the 30th"]);
+    let thread = &output.threads[0];
+    assert_eq!(thread.first_date, "2025-12-01T01:00:00");
+    assert_eq!(thread.message_count, 1);
+    assert_eq!(
+        thread.summary,
+        "Minutes:\n\nFrom the release team, nothing new.\nFrom Dec 1 10:00 2025 the archive is frozen."
+    );
+}
@@ -1,5 +1,12 @@
+#[path = "../common/mod.rs"]
+mod common;
+
 mod discourse;
+mod discourse_recent;
+mod github;
 mod glean;
+mod html;
 mod mailing_list;
+mod manifest;
 mod todoist;
 mod web;
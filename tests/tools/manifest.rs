@@ -0,0 +1,184 @@
+use newsagent::tools::discourse::{DiscourseConfig, DiscourseInstance};
+use newsagent::tools::manifest::{LinkClassifier, LinkKind};
+use newsagent::tools::todoist::{TodoistConfig, TodoistTasksArgs, TodoistTasksTool};
+use newsagent::tools::web::WebConfig;
+use rig::tool::Tool;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn classifier(allowlist: Option<&str>) -> LinkClassifier {
+    LinkClassifier::from_config(
+        &DiscourseConfig {
+            instances: vec![DiscourseInstance {
+                base_url: "discourse.charmhub.io".to_string(),
+                api_key: None,
+                api_username: None,
+            }],
+            ..DiscourseConfig::default()
+        },
+        &WebConfig {
+            allowlist: allowlist.map(str::to_string),
+            ..WebConfig::default()
+        },
+    )
+    .expect("Failed to build classifier")
+}
+
+#[test]
+fn classifies_links_by_tool() {
+    let classifier = classifier(None);
+    let cases = [
+        (
+            "https://github.com/canonical/pebble/releases/tag/v1.27.0",
+            LinkKind::GithubRelease,
+            "github_release",
+        ),
+        (
+            "https://discourse.charmhub.io/t/juju-3-6-9/12345",
+            LinkKind::Discourse,
+            "discourse_fetch",
+        ),
+        (
+            "https://bugs.launchpad.net/juju/+bug/2080000",
+            LinkKind::Launchpad,
+            "browse_web",
+        ),
+        (
+            "https://documentation.ubuntu.com/juju/latest/releasenotes/",
+            LinkKind::Docs,
+            "browse_web",
+        ),
+        (
+            "https://github.com/canonical/pebble/pull/600",
+            LinkKind::Web,
+            "browse_web",
+        ),
+        (
+            "https://discourse.example.org/t/other/1",
+            LinkKind::Web,
+            "browse_web",
+        ),
+    ];
+    for (url, kind, tool) in cases {
+        let link = classifier.classify(url);
+        assert_eq!(link.kind, kind, "{url}");
+        assert_eq!(link.tool, Some(tool), "{url}");
+        assert_eq!(link.unsupported, None, "{url}");
+    }
+}
+
+#[test]
+fn reads_only_discourse_topics_with_the_discourse_tool() {
+    let classifier = classifier(None);
+
+    let topic = classifier.classify("https://discourse.charmhub.io/t/juju-3-6-9/12345/4");
+    assert_eq!(topic.kind, LinkKind::Discourse);
+    assert_eq!(topic.tool, Some("discourse_fetch"));
+
+    for url in [
+        "https://discourse.charmhub.io/c/announcements-and-community/33",
+        "https://discourse.charmhub.io/latest",
+        "https://discourse.charmhub.io/u/juju-team",
+    ] {
+        let link = classifier.classify(url);
+        assert_eq!(link.kind, LinkKind::Web, "{url}");
+        assert_eq!(link.tool, Some("browse_web"), "{url}");
+    }
+}
+
+#[test]
+fn flags_hosts_outside_the_web_allowlist() {
+    let classifier = classifier(Some("ubuntu.com,github.com"));
+
+    let docs = classifier.classify("https://documentation.ubuntu.com/juju/latest/");
+    assert_eq!(docs.tool, Some("browse_web"));
+
+    let release = classifier.classify("https://github.com/canonical/pebble/releases/tag/v1.27.0");
+    assert_eq!(release.tool, Some("github_release"));
+
+    let blog = classifier.classify("https://blog.example.com/post");
+    assert_eq!(blog.kind, LinkKind::Web);
+    assert_eq!(blog.tool, None);
+    assert_eq!(
+        blog.unsupported.as_deref(),
+        Some("blog.example.com is not in NEWSAGENT_WEB_ALLOWLIST")
+    );
+}
+
+#[tokio::test]
+async fn todoist_output_includes_deduplicated_manifest() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/sections"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [
+                {
+                    "id": "1",
+                    "content": "[Pebble 1.27](https://github.com/canonical/pebble/releases/tag/v1.27.0)",
+                    "description": "Docs: https://documentation.ubuntu.com/pebble/latest/, see also https://blog.example.com/post.",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false,
+                    "note_count": 0
+                },
+                {
+                    "id": "2",
+                    "content": "Pebble docs again https://documentation.ubuntu.com/pebble/latest",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 2,
+                    "checked": false,
+                    "note_count": 0
+                }
+            ],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    let tool = TodoistTasksTool::new(TodoistConfig {
+        api_token: "test-token".to_string(),
+        project_id: "proj-1".to_string(),
+        project_section: None,
+        base_url: server.uri(),
+    })
+    .expect("Failed to create Todoist tool")
+    .with_classifier(classifier(Some("ubuntu.com,github.com")));
+
+    let output = tool
+        .call(TodoistTasksArgs { section: None })
+        .await
+        .expect("Todoist tool call failed");
+
+    let urls: Vec<_> = output.links.iter().map(|l| l.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://github.com/canonical/pebble/releases/tag/v1.27.0",
+            "https://documentation.ubuntu.com/pebble/latest/",
+            "https://blog.example.com/post",
+        ]
+    );
+    assert_eq!(output.links[1].tasks.len(), 2);
+    assert_eq!(output.links[2].tool, None);
+
+    let json = serde_json::to_value(&output).expect("Failed to serialise output");
+    assert_eq!(json["links"][0]["kind"], "github_release");
+    assert_eq!(json["links"][0]["tool"], "github_release");
+    assert_eq!(
+        json["links"][2]["unsupported"],
+        "blog.example.com is not in NEWSAGENT_WEB_ALLOWLIST"
+    );
+}
@@ -1,6 +1,9 @@
-use newsagent::tools::todoist::{TodoistConfig, TodoistTasksArgs, TodoistTasksTool};
+use newsagent::tools::todoist::{
+    extract_urls, TaskAction, TodoistConfig, TodoistTasksArgs, TodoistTasksTool,
+};
 use rig::tool::Tool;
-use wiremock::matchers::{header, method, path, query_param};
+use std::time::{Duration, Instant};
+use wiremock::matchers::{body_json, header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[tokio::test]
@@ -226,3 +229,408 @@ async fn renders_filtered_section_without_headers() {
 
     assert_eq!(output.markdown, "- [ ] Task 1");
 }
+
+async fn mount_tidy_fixtures(server: &MockServer, sections: serde_json::Value) {
+    Mock::given(method("GET"))
+        .and(path("/api/v1/sections"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": sections,
+            "next_cursor": null
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [
+                {
+                    "id": "covered",
+                    "content": "[Pebble release](https://github.com/canonical/pebble/releases/tag/v1.27.0)",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false
+                },
+                {
+                    "id": "via-subtask",
+                    "content": "Juju news",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 2,
+                    "checked": false
+                },
+                {
+                    "id": "subtask",
+                    "content": "Thread",
+                    "description": "See https://discourse.example.com/t/juju/42/",
+                    "parent_id": "via-subtask",
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false
+                },
+                {
+                    "id": "missing",
+                    "content": "Not covered https://example.com/skipped",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 3,
+                    "checked": false
+                },
+                {
+                    "id": "no-links",
+                    "content": "Remember to ask about the roadmap",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 4,
+                    "checked": false
+                }
+            ],
+            "next_cursor": null
+        })))
+        .mount(server)
+        .await;
+}
+
+fn tidy_tool(base_url: String) -> TodoistTasksTool {
+    TodoistTasksTool::new(TodoistConfig {
+        api_token: "test-token".to_string(),
+        project_id: "proj-1".to_string(),
+        project_section: None,
+        base_url,
+    })
+    .expect("Failed to create Todoist tool")
+}
+
+const TIDY_DRAFT: &str =
+    "### 🪨 Pebble [1.27.0](https://github.com/canonical/pebble/releases/tag/v1.27.0)\n\n\
+Juju discussed [on Discourse](https://discourse.example.com/t/juju/42).\n";
+
+#[test]
+fn extracts_urls_with_balanced_parentheses() {
+    assert_eq!(
+        extract_urls("See [a](https://x.com/a_(b)) (or https://x.com/c), then https://x.com/d."),
+        ["https://x.com/a_(b)", "https://x.com/c", "https://x.com/d"]
+    );
+}
+
+#[tokio::test]
+async fn closes_tasks_covered_by_the_draft() {
+    let server = MockServer::start().await;
+    mount_tidy_fixtures(&server, serde_json::json!([])).await;
+    for id in ["covered", "via-subtask"] {
+        Mock::given(method("POST"))
+            .and(path(format!("/api/v1/tasks/{id}/close")))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("POST"))
+        .and(path("/api/v1/tasks/missing/close"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let report = tidy_tool(server.uri())
+        .tidy_covered(TIDY_DRAFT, None, TaskAction::Close, "2026-10", false)
+        .await
+        .expect("Tidy failed");
+
+    let ids: Vec<_> = report.changes.iter().map(|c| c.task_id.as_str()).collect();
+    assert_eq!(ids, ["covered", "via-subtask"]);
+    assert_eq!(
+        report.uncovered,
+        ["Not covered https://example.com/skipped".to_string()]
+    );
+    assert!(report.to_string().contains("close: Juju news"));
+}
+
+#[tokio::test]
+async fn moves_covered_tasks_into_a_new_archive_section() {
+    let server = MockServer::start().await;
+    mount_tidy_fixtures(&server, serde_json::json!([])).await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/sections"))
+        .and(body_json(serde_json::json!({
+            "name": "Archived/2026-10",
+            "project_id": "proj-1"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "archive-1",
+            "name": "Archived/2026-10"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    for id in ["covered", "via-subtask"] {
+        Mock::given(method("POST"))
+            .and(path(format!("/api/v1/tasks/{id}/move")))
+            .and(body_json(serde_json::json!({ "section_id": "archive-1" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let report = tidy_tool(server.uri())
+        .tidy_covered(TIDY_DRAFT, None, TaskAction::Move, "2026-10", false)
+        .await
+        .expect("Tidy failed");
+
+    assert_eq!(report.changes.len(), 2);
+    assert!(report
+        .to_string()
+        .contains("move to Archived/2026-10: Juju news"));
+}
+
+#[tokio::test]
+async fn reuses_existing_archive_section() {
+    let server = MockServer::start().await;
+    mount_tidy_fixtures(
+        &server,
+        serde_json::json!([{ "id": "archive-9", "section_order": 5, "name": "Archived/2026-10" }]),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/sections"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_json(serde_json::json!({ "section_id": "archive-9" })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    tidy_tool(server.uri())
+        .tidy_covered(TIDY_DRAFT, None, TaskAction::Move, "2026-10", false)
+        .await
+        .expect("Tidy failed");
+}
+
+#[tokio::test]
+async fn dry_run_reports_without_changing_tasks() {
+    let server = MockServer::start().await;
+    mount_tidy_fixtures(&server, serde_json::json!([])).await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let report = tidy_tool(server.uri())
+        .tidy_covered(TIDY_DRAFT, None, TaskAction::Move, "2026-10", true)
+        .await
+        .expect("Tidy failed");
+
+    let text = report.to_string();
+    assert!(text.starts_with("Todoist dry run, no changes made:"));
+    assert!(text.contains("move to Archived/2026-10: [Pebble release]"));
+    assert!(text.contains("1 task(s) not found in the draft would stay open"));
+}
+
+#[tokio::test]
+async fn renders_labels_priority_due_and_comments() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/sections"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [
+                {
+                    "id": "task-1",
+                    "content": "Juju 3.6.9",
+                    "description": "Fixes CVE-2026-1234",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 1,
+                    "checked": false,
+                    "labels": ["security", "tier1"],
+                    "priority": 4,
+                    "due": { "date": "2026-10-20", "string": "every month", "is_recurring": true },
+                    "note_count": 2
+                },
+                {
+                    "id": "task-2",
+                    "content": "Minor docs update",
+                    "description": "",
+                    "parent_id": null,
+                    "section_id": null,
+                    "child_order": 2,
+                    "checked": false,
+                    "labels": ["skip"],
+                    "priority": 1,
+                    "due": null,
+                    "note_count": 0
+                }
+            ],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/comments"))
+        .and(query_param("task_id", "task-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [
+                {
+                    "id": "c2",
+                    "content": "Also mention the\nupgrade caveat",
+                    "posted_at": "2026-10-03T09:00:00Z"
+                },
+                {
+                    "id": "c1",
+                    "content": "Lead with this one",
+                    "posted_at": "2026-10-02T09:00:00Z"
+                }
+            ],
+            "next_cursor": null
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/comments"))
+        .and(query_param("task_id", "task-2"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let tool = TodoistTasksTool::new(TodoistConfig {
+        api_token: "test-token".to_string(),
+        project_id: "proj-1".to_string(),
+        project_section: None,
+        base_url: server.uri(),
+    })
+    .expect("Failed to create Todoist tool");
+
+    let output = tool
+        .call(TodoistTasksArgs { section: None })
+        .await
+        .expect("Todoist tool call failed");
+
+    assert_eq!(
+        output.markdown,
+        "- [ ] Juju 3.6.9\n\
+         \x20 - **Labels**: security, tier1\n\
+         \x20 - **Priority**: p1\n\
+         \x20 - **Due**: 2026-10-20 (every month)\n\
+         \x20 - **Description**: Fixes CVE-2026-1234\n\
+         \x20 - **Comment (2026-10-02)**: Lead with this one\n\
+         \x20 - **Comment (2026-10-03)**: Also mention the\n\
+         \x20   upgrade caveat\n\
+         - [ ] Minor docs update\n\
+         \x20 - **Labels**: skip"
+    );
+}
+
+#[tokio::test]
+async fn fetches_comments_concurrently_and_skips_failures() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/sections"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": [],
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    let tasks: Vec<_> = (1..=4)
+        .map(|i| {
+            serde_json::json!({
+                "id": format!("task-{i}"),
+                "content": format!("Task {i}"),
+                "description": "",
+                "parent_id": null,
+                "section_id": null,
+                "child_order": i,
+                "checked": false,
+                "note_count": 1
+            })
+        })
+        .collect();
+    Mock::given(method("GET"))
+        .and(path("/api/v1/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": tasks,
+            "next_cursor": null
+        })))
+        .mount(&server)
+        .await;
+
+    for i in [1, 2, 4] {
+        Mock::given(method("GET"))
+            .and(path("/api/v1/comments"))
+            .and(query_param("task_id", format!("task-{i}")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "results": [{
+                            "id": format!("c{i}"),
+                            "content": format!("Note {i}"),
+                            "posted_at": "2026-10-02T09:00:00Z"
+                        }],
+                        "next_cursor": null
+                    }))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/api/v1/comments"))
+        .and(query_param("task_id", "task-3"))
+        .respond_with(ResponseTemplate::new(500).set_delay(Duration::from_millis(500)))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let tool = TodoistTasksTool::new(TodoistConfig {
+        api_token: "test-token".to_string(),
+        project_id: "proj-1".to_string(),
+        project_section: None,
+        base_url: server.uri(),
+    })
+    .expect("Failed to create Todoist tool");
+
+    let start = Instant::now();
+    let output = tool
+        .call(TodoistTasksArgs { section: None })
+        .await
+        .expect("Todoist tool call failed");
+
+    assert!(start.elapsed() < Duration::from_millis(1400));
+    assert_eq!(
+        output.markdown,
+        "- [ ] Task 1\n\
+         \x20 - **Comment (2026-10-02)**: Note 1\n\
+         - [ ] Task 2\n\
+         \x20 - **Comment (2026-10-02)**: Note 2\n\
+         - [ ] Task 3\n\
+         - [ ] Task 4\n\
+         \x20 - **Comment (2026-10-02)**: Note 4"
+    );
+}
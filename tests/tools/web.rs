@@ -1,7 +1,4 @@
-#[path = "../common/mod.rs"]
-mod common;
-
-use common::with_newsagent_env;
+use crate::common::with_newsagent_env;
 use newsagent::tools::web::{
     WebConfig, WebReadabilityArgs, WebReadabilityTool, WebReadabilityToolError,
 };
@@ -44,6 +41,64 @@ async fn fetches_and_truncates_content() {
     assert_eq!(output.source_url, url);
 }
 
+#[tokio::test]
+async fn returns_readable_content_as_markdown() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/release"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<html><head><title>Pebble 1.27</title></head><body><article>
+            <h2>What&rsquo;s new</h2>
+            <p>Pebble 1.27 adds identities, contributed in <a href="https://github.com/canonical/pebble/pull/612">#612</a>. It is a long paragraph so that readability keeps the article body as the main content of this page.</p>
+            <ul><li>Faster restarts when many services share the same plan, thanks to lazy health checks.</li><li>The full list of fixes is in the <a href="/docs/changes">change log</a>, along with upgrade notes for operators.</li></ul>
+            <pre><code class="language-yaml">services:
+  web: {}</code></pre>
+            </article></body></html>"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let tool = WebReadabilityTool::new(WebConfig {
+        allowlist: Some("127.0.0.1".to_string()),
+        ..WebConfig::default()
+    })
+    .expect("Failed to create web tool");
+
+    let output = tool
+        .call(WebReadabilityArgs {
+            url: format!("{}/release", server.uri()),
+        })
+        .await
+        .expect("Web tool call failed");
+
+    assert!(output
+        .text
+        .contains("[#612](https://github.com/canonical/pebble/pull/612)"));
+    assert!(output.text.contains("What’s new"));
+    assert!(output.text.contains("\n- Faster restarts"));
+    assert!(output
+        .text
+        .contains(&format!("[change log]({}/docs/changes)", server.uri())));
+    assert!(output.text.contains("```\nservices:\n  web: {}\n```"));
+    let links: Vec<(&str, String)> = output
+        .links
+        .iter()
+        .map(|l| (l.text.as_str(), l.url.clone()))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (
+                "#612",
+                "https://github.com/canonical/pebble/pull/612".to_string()
+            ),
+            ("change log", format!("{}/docs/changes", server.uri())),
+        ]
+    );
+    assert!(!output.truncated);
+}
+
 #[tokio::test]
 async fn rejects_disallowed_host() {
     let server = MockServer::start().await;
@@ -158,3 +213,52 @@ async fn uses_config_from_environment() {
     assert!(output.text.chars().count() <= 4);
     assert!(output.truncated);
 }
+
+#[tokio::test]
+async fn leaves_out_same_page_links_and_caps_the_list() {
+    let server = MockServer::start().await;
+    let nav: String = (0..200)
+        .map(|i| {
+            format!("<p>Release {i} of the tool improves start-up time and memory use; read <a href=\"/docs/page-{i}\">the notes</a> for details.</p>")
+        })
+        .collect();
+    let body = format!(
+        r##"<html><head><title>Docs</title></head><body><article>
+        <p>See <a href="#install">Installing</a>, <a href="">this page</a> and <a href="/docs/other#setup">setup</a>. This paragraph is long enough for readability to keep the article as the main content.</p>
+        {nav}
+        </article></body></html>"##
+    );
+    Mock::given(method("GET"))
+        .and(path("/docs/index"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    let tool = WebReadabilityTool::new(WebConfig {
+        allowlist: Some("127.0.0.1".to_string()),
+        max_chars: Some(4000),
+        ..WebConfig::default()
+    })
+    .expect("Failed to create web tool");
+
+    let output = tool
+        .call(WebReadabilityArgs {
+            url: format!("{}/docs/index", server.uri()),
+        })
+        .await
+        .expect("Web tool call failed");
+
+    assert_eq!(
+        output.links[0].url,
+        format!("{}/docs/other#setup", server.uri())
+    );
+    assert!(output.links.iter().all(|l| !l.url.contains("/docs/index")));
+    let link_chars: usize = output
+        .links
+        .iter()
+        .map(|l| l.text.chars().count() + l.url.chars().count())
+        .sum();
+    assert!(link_chars <= 1000);
+    assert_eq!(output.links.len() + output.omitted_links, 201);
+    assert!(output.text.chars().count() + link_chars <= 4000);
+}